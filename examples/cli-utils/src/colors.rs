@@ -14,94 +14,713 @@
 //!
 //! The above example demonstrates how to use the color functions to generate colorized strings and print them to the terminal.
 //!
-//! # Code
+//! Escape codes are only emitted when colors are enabled for stdout; see `ColorChoice`
+//! and `set_color_choice`. The examples below force them on.
 //!
-//! ```rust
-//! pub fn red(s: &str) -> String {
-//!     format!("\x1b[31m{}\x1b[0m", s)
-//! }
+//! Colors and attributes can be combined with a `Style`:
 //!
-//! pub fn green(s: &str) -> String {
-//!     format!("\x1b[32m{}\x1b[0m", s)
-//! }
-//!
-//! pub fn blue(s: &str) -> String {
-//!     format!("\x1b[34m{}\x1b[0m", s)
-//! }
-//!
-//! pub fn bold(s: &str) -> String {
-//!     format!("\x1b[1m{}\x1b[0m", s)
-//! }
-//!
-//! pub fn reset(s: &str) -> String {
-//!     format!("\x1b[0m{}\x1b[0m", s)
-//! }
-//!
-//! pub enum Color {
-//!     Red,
-//!     Green,
-//!     Blue,
-//!     Bold,
-//! }
-//!
-//! pub struct ColorString {
-//!     pub color: Color,
-//!     pub string: String,
-//!     pub colorized: String,
-//! }
-//!
-//! impl ColorString {
-//!      Paints the colorized string based on the color field.
-//!     
-//!      This method takes the value of the `color` field and applies the corresponding color to the `string` field,
-//!      generating a colorized string and assigning it to the `colorized` field.
-//!     
-//!      # Examples
-//!     
-//!      ```
-//!      use cli_utils::colors::*;
-//!     
-//!      let mut color_string = ColorString {
-//!          color: Color::Red,
-//!          string: String::from("Hello, world!"),
-//!          colorized: String::new(),
-//!      };
-//!     
-//!      color_string.paint();
-//!     
-//!      assert_eq!(color_string.colorized, red("Hello, world!"));
-//!      ```
-//!     pub fn paint(&mut self) {
-//!         match self.color {
-//!             Color::Red => self.colorized = red(&self.string),
-//!             Color::Green => self.colorized = green(&self.string),
-//!             Color::Blue => self.colorized = blue(&self.string),
-//!             Color::Bold => self.colorized = bold(&self.string),
-//!         };
-//!     }
+//! ```
+//! use cli_utils::colors::{set_color_choice, Color, ColorChoice, Style};
 //!
-//!      Resets the colorized string to its original state.
-//!     
-//!      This method resets the `colorized` field to the original `string` value, removing any applied color or style.
-//!     
-//!      # Examples
-//!     
-//!      ```
-//!      use cli_utils::colors::*;
-//!     
-//!      let mut color_string = ColorString {
-//!          color: Color::Red,
-//!          string: String::from("Hello, world!"),
-//!          colorized: String::new(),
-//!      };
-//!     
-//!      color_string.paint();
-//!      color_string.reset();
-//!     
-//!      assert_eq!(color_string.colorized, color_string.string);
-//!      ```
-//!     pub fn reset(&mut self) {
-//!         self.colorized = reset(&self.string);
-//!     }
-//! }
+//! set_color_choice(ColorChoice::Always);
+//! let style = Style::new().fg(Color::Red).bold();
+//! assert_eq!(style.paint("error"), "\x1b[1;31merror\x1b[0m");
 //! ```
+
+use std::fmt;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+mod ansi;
+mod choice;
+mod markup;
+mod palette;
+mod theme;
+
+pub use ansi::{display_width, pad_to_width, strip_ansi, truncate_to_width};
+pub use markup::{escape_markup, markup, markup_to, MarkupError, MarkupErrorKind};
+pub use theme::{Role, Theme, ThemeError};
+
+pub use choice::{
+    color_choice, colors_enabled, set_color_choice, ColorChoice, ParseColorChoiceError, Stream,
+};
+
+pub(crate) const RESET: &str = "\x1b[0m";
+
+/// A terminal color: one of the 16 basic colors, a 256-color palette index or a 24-bit RGB value.
+///
+/// Colors can be parsed from names, palette indices and hex strings:
+/// ```
+/// use cli_utils::colors::Color;
+/// assert_eq!("#ff8800".parse::<Color>().unwrap(), Color::Rgb(255, 136, 0));
+/// assert_eq!("#f80".parse::<Color>().unwrap(), Color::Rgb(255, 136, 0));
+/// assert_eq!("208".parse::<Color>().unwrap(), Color::Ansi256(208));
+/// assert_eq!("bright-red".parse::<Color>().unwrap(), Color::BrightRed);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Color {
+    Black,
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+    White,
+    BrightBlack,
+    BrightRed,
+    BrightGreen,
+    BrightYellow,
+    BrightBlue,
+    BrightMagenta,
+    BrightCyan,
+    BrightWhite,
+    Ansi256(u8),
+    Rgb(u8, u8, u8),
+}
+
+const BASIC_COLORS: [Color; 16] = [
+    Color::Black,
+    Color::Red,
+    Color::Green,
+    Color::Yellow,
+    Color::Blue,
+    Color::Magenta,
+    Color::Cyan,
+    Color::White,
+    Color::BrightBlack,
+    Color::BrightRed,
+    Color::BrightGreen,
+    Color::BrightYellow,
+    Color::BrightBlue,
+    Color::BrightMagenta,
+    Color::BrightCyan,
+    Color::BrightWhite,
+];
+
+const COLOR_NAMES: [&str; 16] = [
+    "black",
+    "red",
+    "green",
+    "yellow",
+    "blue",
+    "magenta",
+    "cyan",
+    "white",
+    "bright-black",
+    "bright-red",
+    "bright-green",
+    "bright-yellow",
+    "bright-blue",
+    "bright-magenta",
+    "bright-cyan",
+    "bright-white",
+];
+
+impl Color {
+    /// Parses a `#rrggbb` or `#rgb` hex string into a `Color::Rgb`.
+    /// # Examples:
+    /// ```
+    /// use cli_utils::colors::Color;
+    /// assert_eq!(Color::from_hex("#0a0B0c").unwrap(), Color::Rgb(10, 11, 12));
+    /// assert!(Color::from_hex("#12345").is_err());
+    /// ```
+    pub fn from_hex(s: &str) -> Result<Color, ParseColorError> {
+        let error = || ParseColorError(s.to_string());
+        let digits = s.strip_prefix('#').ok_or_else(error)?;
+        if !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(error());
+        }
+        let channel = |i: usize, len: usize| {
+            let value = u8::from_str_radix(&digits[i * len..(i + 1) * len], 16).unwrap_or(0);
+            if len == 1 {
+                value * 17
+            } else {
+                value
+            }
+        };
+        match digits.len() {
+            6 => Ok(Color::Rgb(channel(0, 2), channel(1, 2), channel(2, 2))),
+            3 => Ok(Color::Rgb(channel(0, 1), channel(1, 1), channel(2, 1))),
+            _ => Err(error()),
+        }
+    }
+
+    /// Returns the position of a basic color in SGR order, or `None` for extended colors.
+    fn basic_index(self) -> Option<u8> {
+        BASIC_COLORS
+            .iter()
+            .position(|c| *c == self)
+            .map(|i| i as u8)
+    }
+
+    /// Converts the color to the closest one representable at the given depth.
+    ///
+    /// Basic colors are returned unchanged at every depth.
+    /// # Examples:
+    /// ```
+    /// use cli_utils::colors::{Color, ColorDepth};
+    /// let orange = Color::Rgb(255, 136, 0);
+    /// assert_eq!(orange.downsample(ColorDepth::TrueColor), orange);
+    /// assert_eq!(orange.downsample(ColorDepth::Ansi256), Color::Ansi256(208));
+    /// assert_eq!(orange.downsample(ColorDepth::Ansi16), Color::Yellow);
+    /// ```
+    pub fn downsample(self, depth: ColorDepth) -> Color {
+        match (self, depth) {
+            (Color::Rgb(r, g, b), ColorDepth::Ansi256) => {
+                Color::Ansi256(palette::rgb_to_ansi256((r, g, b)))
+            }
+            (Color::Rgb(r, g, b), ColorDepth::Ansi16) => {
+                BASIC_COLORS[palette::rgb_to_ansi16((r, g, b)) as usize]
+            }
+            (Color::Ansi256(index), ColorDepth::Ansi16) => {
+                BASIC_COLORS[palette::rgb_to_ansi16(palette::ansi256_to_rgb(index)) as usize]
+            }
+            _ => self,
+        }
+    }
+
+    /// Returns the SGR parameters selecting this color as the foreground or background.
+    fn codes(self, background: bool) -> String {
+        let base = if background { 40 } else { 30 };
+        match self {
+            Color::Ansi256(index) => format!("{};5;{}", base + 8, index),
+            Color::Rgb(r, g, b) => format!("{};2;{};{};{}", base + 8, r, g, b),
+            basic => {
+                let index = basic.basic_index().unwrap_or(0);
+                if index < 8 {
+                    (base + index).to_string()
+                } else {
+                    (base + 60 + index - 8).to_string()
+                }
+            }
+        }
+    }
+}
+
+impl FromStr for Color {
+    type Err = ParseColorError;
+
+    /// Parses a color name (`red`, `bright-blue`, `grey`), a palette index (`0`-`255`)
+    /// or a hex string (`#ff8800`, `#f80`). Names are case-insensitive.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.starts_with('#') {
+            return Color::from_hex(s);
+        }
+        if let Ok(index) = s.parse::<u8>() {
+            return Ok(Color::Ansi256(index));
+        }
+        let name = s.to_ascii_lowercase().replace('_', "-");
+        let name = match name.as_str() {
+            "gray" | "grey" => "bright-black".to_string(),
+            // "brightred" is accepted as well as "bright-red".
+            other => match other.strip_prefix("bright") {
+                Some(rest) if !rest.starts_with('-') => format!("bright-{}", rest),
+                _ => name,
+            },
+        };
+        COLOR_NAMES
+            .iter()
+            .position(|n| *n == name)
+            .map(|i| BASIC_COLORS[i])
+            .ok_or_else(|| ParseColorError(s.to_string()))
+    }
+}
+
+/// The error returned when a string cannot be parsed as a `Color`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseColorError(String);
+
+impl fmt::Display for ParseColorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid color {:?}: expected a color name, a palette index 0-255 or a hex value like #ff8800",
+            self.0
+        )
+    }
+}
+
+impl std::error::Error for ParseColorError {}
+
+/// How many colors the terminal can display.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ColorDepth {
+    Ansi16,
+    Ansi256,
+    TrueColor,
+}
+
+impl ColorDepth {
+    /// Guesses the color depth from the `COLORTERM` and `TERM` environment variables.
+    pub fn detect() -> ColorDepth {
+        let colorterm = std::env::var("COLORTERM").unwrap_or_default();
+        let term = std::env::var("TERM").unwrap_or_default();
+        Self::from_env_values(&colorterm, &term)
+    }
+
+    fn from_env_values(colorterm: &str, term: &str) -> ColorDepth {
+        if colorterm == "truecolor" || colorterm == "24bit" {
+            ColorDepth::TrueColor
+        } else if term.contains("256color") {
+            ColorDepth::Ansi256
+        } else {
+            ColorDepth::Ansi16
+        }
+    }
+}
+
+/// 0 means "not set yet"; otherwise the depth is stored as its discriminant plus one.
+static COLOR_DEPTH: AtomicU8 = AtomicU8::new(0);
+
+/// Returns the process-wide color depth, detecting it from the environment on first use.
+pub fn color_depth() -> ColorDepth {
+    match COLOR_DEPTH.load(Ordering::Relaxed) {
+        1 => ColorDepth::Ansi16,
+        2 => ColorDepth::Ansi256,
+        3 => ColorDepth::TrueColor,
+        _ => {
+            let depth = ColorDepth::detect();
+            set_color_depth(depth);
+            depth
+        }
+    }
+}
+
+/// Overrides the process-wide color depth used by `Style::paint`.
+pub fn set_color_depth(depth: ColorDepth) {
+    COLOR_DEPTH.store(depth as u8 + 1, Ordering::Relaxed);
+}
+
+/// A text attribute that can be switched on in a `Style`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Attribute {
+    Bold,
+    Dim,
+    Italic,
+    Underline,
+    Blink,
+    Reverse,
+    Strikethrough,
+}
+
+impl Attribute {
+    const ALL: [Attribute; 7] = [
+        Attribute::Bold,
+        Attribute::Dim,
+        Attribute::Italic,
+        Attribute::Underline,
+        Attribute::Blink,
+        Attribute::Reverse,
+        Attribute::Strikethrough,
+    ];
+
+    const fn bit(self) -> u8 {
+        1 << self as u8
+    }
+
+    fn code(self) -> &'static str {
+        match self {
+            Attribute::Bold => "1",
+            Attribute::Dim => "2",
+            Attribute::Italic => "3",
+            Attribute::Underline => "4",
+            Attribute::Blink => "5",
+            Attribute::Reverse => "7",
+            Attribute::Strikethrough => "9",
+        }
+    }
+}
+
+/// A set of `Attribute`s.
+/// # Examples:
+/// ```
+/// use cli_utils::colors::{Attribute, Attributes};
+/// let attrs = Attributes::new().with(Attribute::Bold).with(Attribute::Underline);
+/// assert!(attrs.contains(Attribute::Bold));
+/// assert!(!attrs.contains(Attribute::Italic));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Attributes(u8);
+
+impl Attributes {
+    /// Creates an empty set.
+    pub const fn new() -> Self {
+        Self(0)
+    }
+
+    /// Returns the set with `attr` added.
+    pub const fn with(self, attr: Attribute) -> Self {
+        Self(self.0 | attr.bit())
+    }
+
+    /// Adds `attr` to the set.
+    pub fn insert(&mut self, attr: Attribute) {
+        self.0 |= attr.bit();
+    }
+
+    /// Removes `attr` from the set.
+    pub fn remove(&mut self, attr: Attribute) {
+        self.0 &= !attr.bit();
+    }
+
+    pub fn contains(&self, attr: Attribute) -> bool {
+        self.0 & attr.bit() != 0
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// Iterates over the attributes in the set, in SGR code order.
+    pub fn iter(&self) -> impl Iterator<Item = Attribute> + '_ {
+        Attribute::ALL
+            .into_iter()
+            .filter(|attr| self.contains(*attr))
+    }
+}
+
+/// A foreground color, a background color and a set of attributes.
+///
+/// A `Style` is built up with its builder methods and renders as a single SGR sequence.
+/// # Examples:
+/// ```
+/// use cli_utils::colors::{Color, Style};
+/// let style = Style::new().fg(Color::Yellow).bg(Color::Blue).underline();
+/// assert_eq!(style.prefix(), "\x1b[4;33;44m");
+/// assert_eq!(Style::new().paint("plain"), "plain");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Style {
+    pub foreground: Option<Color>,
+    pub background: Option<Color>,
+    pub attributes: Attributes,
+}
+
+impl Style {
+    /// Creates a style with no colors and no attributes.
+    pub const fn new() -> Self {
+        Self {
+            foreground: None,
+            background: None,
+            attributes: Attributes::new(),
+        }
+    }
+
+    pub const fn fg(mut self, color: Color) -> Self {
+        self.foreground = Some(color);
+        self
+    }
+
+    pub const fn bg(mut self, color: Color) -> Self {
+        self.background = Some(color);
+        self
+    }
+
+    pub const fn attr(mut self, attr: Attribute) -> Self {
+        self.attributes = self.attributes.with(attr);
+        self
+    }
+
+    pub const fn bold(self) -> Self {
+        self.attr(Attribute::Bold)
+    }
+
+    pub const fn dim(self) -> Self {
+        self.attr(Attribute::Dim)
+    }
+
+    pub const fn italic(self) -> Self {
+        self.attr(Attribute::Italic)
+    }
+
+    pub const fn underline(self) -> Self {
+        self.attr(Attribute::Underline)
+    }
+
+    pub const fn blink(self) -> Self {
+        self.attr(Attribute::Blink)
+    }
+
+    pub const fn reverse(self) -> Self {
+        self.attr(Attribute::Reverse)
+    }
+
+    pub const fn strikethrough(self) -> Self {
+        self.attr(Attribute::Strikethrough)
+    }
+
+    /// Returns true if the style has no colors and no attributes.
+    pub fn is_plain(&self) -> bool {
+        self.foreground.is_none() && self.background.is_none() && self.attributes.is_empty()
+    }
+
+    /// Returns the SGR sequence that switches this style on, or an empty string for a plain style.
+    ///
+    /// Attributes come first, followed by the foreground and background colors.
+    /// Colors are written as-is, without downsampling.
+    pub fn prefix(&self) -> String {
+        self.prefix_for(ColorDepth::TrueColor)
+    }
+
+    /// Returns the SGR sequence for this style with its colors downsampled to `depth`.
+    /// # Examples:
+    /// ```
+    /// use cli_utils::colors::{Color, ColorDepth, Style};
+    /// let style = Style::new().fg(Color::Rgb(255, 136, 0)).bg(Color::Ansi256(17));
+    /// assert_eq!(style.prefix_for(ColorDepth::TrueColor), "\x1b[38;2;255;136;0;48;5;17m");
+    /// assert_eq!(style.prefix_for(ColorDepth::Ansi256), "\x1b[38;5;208;48;5;17m");
+    /// assert_eq!(style.prefix_for(ColorDepth::Ansi16), "\x1b[33;40m");
+    /// ```
+    pub fn prefix_for(&self, depth: ColorDepth) -> String {
+        if self.is_plain() {
+            return String::new();
+        }
+        let mut codes: Vec<String> = self
+            .attributes
+            .iter()
+            .map(|a| a.code().to_string())
+            .collect();
+        if let Some(color) = self.foreground {
+            codes.push(color.downsample(depth).codes(false));
+        }
+        if let Some(color) = self.background {
+            codes.push(color.downsample(depth).codes(true));
+        }
+        format!("\x1b[{}m", codes.join(";"))
+    }
+
+    /// Wraps the string in this style, followed by a reset code, if colors are enabled for stdout.
+    ///
+    /// Colors are downsampled to the process-wide `color_depth()`.
+    /// A plain style, or disabled colors, return the string unchanged.
+    pub fn paint(&self, s: &str) -> String {
+        self.paint_to(s, Stream::Stdout)
+    }
+
+    /// Like `paint`, but checks whether colors are enabled for `stream`.
+    /// # Examples:
+    /// ```
+    /// use cli_utils::colors::{set_color_choice, Color, ColorChoice, Stream, Style};
+    /// set_color_choice(ColorChoice::Never);
+    /// assert_eq!(Style::new().fg(Color::Red).paint_to("oops", Stream::Stderr), "oops");
+    /// ```
+    pub fn paint_to(&self, s: &str, stream: Stream) -> String {
+        if !colors_enabled(stream) {
+            return s.to_string();
+        }
+        self.paint_for(s, color_depth())
+    }
+
+    /// Wraps the string in this style with its colors downsampled to `depth`,
+    /// regardless of the color choice.
+    pub fn paint_for(&self, s: &str, depth: ColorDepth) -> String {
+        if self.is_plain() {
+            return s.to_string();
+        }
+        format!("{}{}{}", self.prefix_for(depth), s, RESET)
+    }
+}
+
+impl From<Color> for Style {
+    fn from(color: Color) -> Self {
+        Style::new().fg(color)
+    }
+}
+
+impl FromStr for Attribute {
+    type Err = ParseStyleError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "bold" => Ok(Attribute::Bold),
+            "dim" => Ok(Attribute::Dim),
+            "italic" => Ok(Attribute::Italic),
+            "underline" => Ok(Attribute::Underline),
+            "blink" => Ok(Attribute::Blink),
+            "reverse" => Ok(Attribute::Reverse),
+            "strikethrough" | "strike" => Ok(Attribute::Strikethrough),
+            _ => Err(ParseStyleError(format!("unknown attribute {:?}", s))),
+        }
+    }
+}
+
+impl FromStr for Style {
+    type Err = ParseStyleError;
+
+    /// Parses a space-separated list of attributes and colors, such as `bold red on #202020`.
+    ///
+    /// A color after `on` becomes the background; any other color becomes the foreground.
+    /// # Examples:
+    /// ```
+    /// use cli_utils::colors::{Color, Style};
+    /// let style: Style = "bold red on #202020".parse().unwrap();
+    /// assert_eq!(style, Style::new().bold().fg(Color::Red).bg(Color::Rgb(32, 32, 32)));
+    /// assert!("bold on".parse::<Style>().is_err());
+    /// ```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut style = Style::new();
+        let mut words = s.split_whitespace();
+        while let Some(word) = words.next() {
+            if word.eq_ignore_ascii_case("on") {
+                let color = words
+                    .next()
+                    .ok_or_else(|| ParseStyleError("expected a color after \"on\"".to_string()))?;
+                style = style.bg(color.parse()?);
+            } else if let Ok(attr) = word.parse::<Attribute>() {
+                style = style.attr(attr);
+            } else {
+                let color = word.parse().map_err(|_| {
+                    ParseStyleError(format!("{:?} is neither a color nor an attribute", word))
+                })?;
+                style = style.fg(color);
+            }
+        }
+        Ok(style)
+    }
+}
+
+/// The error returned when a string cannot be parsed as a `Style` or an `Attribute`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseStyleError(String);
+
+impl fmt::Display for ParseStyleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid style: {}", self.0)
+    }
+}
+
+impl std::error::Error for ParseStyleError {}
+
+impl From<ParseColorError> for ParseStyleError {
+    fn from(error: ParseColorError) -> Self {
+        ParseStyleError(error.to_string())
+    }
+}
+
+/// Wraps the string in the ANSI escape code for red text, if colors are enabled for stdout.
+/// # Examples:
+/// ```
+/// use cli_utils::colors::red;
+/// # cli_utils::colors::set_color_choice(cli_utils::colors::ColorChoice::Always);
+/// assert_eq!(red("Red"), "\x1b[31mRed\x1b[0m");
+/// ```
+pub fn red(s: &str) -> String {
+    Style::new().fg(Color::Red).paint(s)
+}
+
+/// Wraps the string in the ANSI escape code for green text, if colors are enabled for stdout.
+/// # Examples:
+/// ```
+/// use cli_utils::colors::green;
+/// # cli_utils::colors::set_color_choice(cli_utils::colors::ColorChoice::Always);
+/// assert_eq!(green("Green"), "\x1b[32mGreen\x1b[0m");
+/// ```
+pub fn green(s: &str) -> String {
+    Style::new().fg(Color::Green).paint(s)
+}
+
+/// Wraps the string in the ANSI escape code for blue text, if colors are enabled for stdout.
+/// # Examples:
+/// ```
+/// use cli_utils::colors::blue;
+/// # cli_utils::colors::set_color_choice(cli_utils::colors::ColorChoice::Always);
+/// assert_eq!(blue("Blue"), "\x1b[34mBlue\x1b[0m");
+/// ```
+pub fn blue(s: &str) -> String {
+    Style::new().fg(Color::Blue).paint(s)
+}
+
+/// Wraps the string in the ANSI escape code for bold text, if colors are enabled for stdout.
+/// # Examples:
+/// ```
+/// use cli_utils::colors::bold;
+/// # cli_utils::colors::set_color_choice(cli_utils::colors::ColorChoice::Always);
+/// assert_eq!(bold("Bold"), "\x1b[1mBold\x1b[0m");
+/// ```
+pub fn bold(s: &str) -> String {
+    Style::new().bold().paint(s)
+}
+
+/// Wraps the string in reset codes, clearing any style that was active before it,
+/// if colors are enabled for stdout.
+/// # Examples:
+/// ```
+/// use cli_utils::colors::reset;
+/// # cli_utils::colors::set_color_choice(cli_utils::colors::ColorChoice::Always);
+/// assert_eq!(reset("Plain"), "\x1b[0mPlain\x1b[0m");
+/// ```
+pub fn reset(s: &str) -> String {
+    if !colors_enabled(Stream::Stdout) {
+        return s.to_string();
+    }
+    format!("{}{}{}", RESET, s, RESET)
+}
+
+/// A string together with the style it should be painted with.
+/// # Examples:
+/// ```
+/// use cli_utils::colors::{Color, ColorString, Style};
+/// let color_string = ColorString {
+///     style: Style::new().fg(Color::Green).bold(),
+///     string: String::from("ok"),
+///     colorized: String::new(),
+/// };
+/// ```
+pub struct ColorString {
+    pub style: Style,
+    pub string: String,
+    pub colorized: String,
+}
+
+impl ColorString {
+    /// Paints the colorized string based on the style field.
+    ///
+    /// This method applies the `style` field to the `string` field,
+    /// generating a colorized string and assigning it to the `colorized` field.
+    /// When colors are disabled for stdout, `colorized` is the plain string.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cli_utils::colors::*;
+    ///
+    /// set_color_choice(ColorChoice::Always);
+    /// let mut color_string = ColorString {
+    ///     style: Color::Red.into(),
+    ///     string: String::from("Hello, world!"),
+    ///     colorized: String::new(),
+    /// };
+    ///
+    /// color_string.paint();
+    ///
+    /// assert_eq!(color_string.colorized, red("Hello, world!"));
+    /// ```
+    pub fn paint(&mut self) {
+        self.colorized = self.style.paint(&self.string);
+    }
+
+    /// Resets the colorized string to its original state.
+    ///
+    /// This method resets the `colorized` field to the original `string` value, removing any applied color or style.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cli_utils::colors::*;
+    ///
+    /// let mut color_string = ColorString {
+    ///     style: Style::new().fg(Color::Red).bold(),
+    ///     string: String::from("Hello, world!"),
+    ///     colorized: String::new(),
+    /// };
+    ///
+    /// color_string.paint();
+    /// color_string.reset();
+    ///
+    /// assert_eq!(color_string.colorized, color_string.string);
+    /// ```
+    pub fn reset(&mut self) {
+        self.colorized = self.string.clone();
+    }
+}
@@ -0,0 +1,226 @@
+//! Stripping escape sequences and measuring the visible width of colored text.
+
+use std::borrow::Cow;
+use unicode_width::UnicodeWidthStr;
+
+const ESC: char = '\x1b';
+const CSI: char = '\u{9b}';
+
+/// A run of visible text or a single escape sequence.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Segment<'a> {
+    Text(&'a str),
+    Escape(&'a str),
+}
+
+/// Splits a string into visible text and escape sequences.
+pub(crate) fn segments(s: &str) -> Vec<Segment<'_>> {
+    let mut result = Vec::new();
+    let mut text_start = 0;
+    let mut i = 0;
+    while i < s.len() {
+        let c = s[i..].chars().next().unwrap_or_default();
+        if c == ESC || c == CSI {
+            let end = escape_end(s, i);
+            if text_start < i {
+                result.push(Segment::Text(&s[text_start..i]));
+            }
+            result.push(Segment::Escape(&s[i..end]));
+            i = end;
+            text_start = end;
+        } else {
+            i += c.len_utf8();
+        }
+    }
+    if text_start < s.len() {
+        result.push(Segment::Text(&s[text_start..]));
+    }
+    result
+}
+
+/// Returns the byte offset just past the escape sequence starting at `start`.
+///
+/// Unterminated sequences extend to the end of the string.
+fn escape_end(s: &str, start: usize) -> usize {
+    let bytes = s.as_bytes();
+    let (mut i, kind) = if s[start..].starts_with(CSI) {
+        (start + CSI.len_utf8(), b'[')
+    } else {
+        match bytes.get(start + 1) {
+            Some(&b) => (start + 2, b),
+            None => return bytes.len(),
+        }
+    };
+    match kind {
+        // CSI: parameter and intermediate bytes, then one final byte.
+        b'[' => {
+            while i < bytes.len() && (0x20..=0x3f).contains(&bytes[i]) {
+                i += 1;
+            }
+            if i < bytes.len() && (0x40..=0x7e).contains(&bytes[i]) {
+                i += 1;
+            }
+            i
+        }
+        // OSC (hyperlinks, titles), DCS, SOS, PM and APC: terminated by BEL or ST.
+        b']' | b'P' | b'X' | b'^' | b'_' => {
+            while i < bytes.len() {
+                if bytes[i] == 0x07 {
+                    return i + 1;
+                }
+                if bytes[i] == 0x1b && bytes.get(i + 1) == Some(&b'\\') {
+                    return i + 2;
+                }
+                i += 1;
+            }
+            i
+        }
+        // Other escapes: optional intermediate bytes, then one final byte.
+        b if (0x20..=0x2f).contains(&b) => {
+            while i < bytes.len() && (0x20..=0x2f).contains(&bytes[i]) {
+                i += 1;
+            }
+            if i < bytes.len() && (0x30..=0x7e).contains(&bytes[i]) {
+                i += 1;
+            }
+            i
+        }
+        b if b.is_ascii() => i,
+        // ESC followed by a multi-byte character: only the ESC is dropped.
+        _ => start + 1,
+    }
+}
+
+/// Removes SGR codes, other CSI sequences, OSC 8 hyperlinks and other escape sequences.
+///
+/// Returns the input unchanged, without allocating, when it contains no escapes.
+/// # Examples:
+/// ```
+/// use cli_utils::colors::strip_ansi;
+/// assert_eq!(strip_ansi("\x1b[1;31merror\x1b[0m: \x1b[2Kdone"), "error: done");
+/// let link = "\x1b]8;;https://example.com\x1b\\site\x1b]8;;\x1b\\";
+/// assert_eq!(strip_ansi(link), "site");
+/// ```
+pub fn strip_ansi(s: &str) -> Cow<'_, str> {
+    if !s.contains([ESC, CSI]) {
+        return Cow::Borrowed(s);
+    }
+    let mut stripped = String::with_capacity(s.len());
+    for segment in segments(s) {
+        if let Segment::Text(text) = segment {
+            stripped.push_str(text);
+        }
+    }
+    Cow::Owned(stripped)
+}
+
+/// Returns the number of terminal columns the string occupies.
+///
+/// Escape sequences take no space, East Asian wide characters and emoji take two
+/// columns, and combining marks take none.
+/// # Examples:
+/// ```
+/// use cli_utils::colors::display_width;
+/// assert_eq!(display_width("\x1b[31mred\x1b[0m"), 3);
+/// assert_eq!(display_width("日本"), 4);
+/// assert_eq!(display_width("e\u{301}"), 1);
+/// assert_eq!(display_width("🦀"), 2);
+/// ```
+pub fn display_width(s: &str) -> usize {
+    segments(s)
+        .into_iter()
+        .map(|segment| match segment {
+            Segment::Text(text) => text.width(),
+            Segment::Escape(_) => 0,
+        })
+        .sum()
+}
+
+/// Pads the string with spaces on the right until it is `width` columns wide.
+///
+/// Strings that are already wide enough are returned unchanged.
+/// # Examples:
+/// ```
+/// use cli_utils::colors::pad_to_width;
+/// assert_eq!(pad_to_width("\x1b[31mab\x1b[0m", 4), "\x1b[31mab\x1b[0m  ");
+/// assert_eq!(pad_to_width("日本", 3), "日本");
+/// ```
+pub fn pad_to_width(s: &str, width: usize) -> String {
+    let padding = width.saturating_sub(display_width(s));
+    format!("{}{}", s, " ".repeat(padding))
+}
+
+/// Cuts the string down to at most `width` columns, keeping its escape sequences.
+///
+/// A wide character that would straddle the limit is dropped. If the string contained
+/// any escape sequence and was cut, a reset code is appended so the style does not leak.
+/// # Examples:
+/// ```
+/// use cli_utils::colors::truncate_to_width;
+/// assert_eq!(truncate_to_width("\x1b[31mhello\x1b[0m", 3), "\x1b[31mhel\x1b[0m");
+/// assert_eq!(truncate_to_width("日本語", 3), "日");
+/// assert_eq!(truncate_to_width("short", 10), "short");
+/// ```
+pub fn truncate_to_width(s: &str, width: usize) -> String {
+    if display_width(s) <= width {
+        return s.to_string();
+    }
+    let mut result = String::with_capacity(s.len());
+    let mut used = 0;
+    let mut styled = false;
+    'segments: for segment in segments(s) {
+        match segment {
+            Segment::Escape(escape) => {
+                styled = true;
+                result.push_str(escape);
+            }
+            Segment::Text(text) => {
+                for (i, c) in text.char_indices() {
+                    let c_width = text[i..i + c.len_utf8()].width();
+                    if used + c_width > width {
+                        break 'segments;
+                    }
+                    used += c_width;
+                    result.push(c);
+                }
+            }
+        }
+    }
+    if styled {
+        result.push_str(super::RESET);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_segments_split_text_and_escapes() {
+        assert_eq!(
+            segments("a\x1b[1mb\x1b]0;title\x07c"),
+            vec![
+                Segment::Text("a"),
+                Segment::Escape("\x1b[1m"),
+                Segment::Text("b"),
+                Segment::Escape("\x1b]0;title\x07"),
+                Segment::Text("c"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_strip_ansi_handles_other_escapes() {
+        assert_eq!(strip_ansi("\x1b(Babc"), "abc");
+        assert_eq!(strip_ansi("\u{9b}31mabc"), "abc");
+        assert_eq!(strip_ansi("a\x1b[?25lb\x1b[10;20Hc"), "abc");
+        assert_eq!(strip_ansi("unterminated\x1b[31"), "unterminated");
+        assert_eq!(strip_ansi("trailing\x1b"), "trailing");
+    }
+
+    #[test]
+    fn test_strip_ansi_borrows_plain_strings() {
+        assert!(matches!(strip_ansi("plain"), Cow::Borrowed("plain")));
+    }
+}
@@ -0,0 +1,179 @@
+//! Deciding whether escape codes should be written to stdout and stderr.
+
+use std::fmt;
+use std::io::IsTerminal;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// Whether colored output should be produced.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColorChoice {
+    /// Color only when the environment and the stream allow it.
+    #[default]
+    Auto,
+    /// Always emit escape codes.
+    Always,
+    /// Never emit escape codes.
+    Never,
+}
+
+/// An output stream whose color support can be queried.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stream {
+    Stdout,
+    Stderr,
+}
+
+impl ColorChoice {
+    /// Decides whether output written to `stream` should be colored.
+    ///
+    /// Under `Auto` this honors, in order: `NO_COLOR` (any non-empty value disables color),
+    /// `CLICOLOR_FORCE` (any value other than `0` enables color), `CLICOLOR=0`, `TERM=dumb`,
+    /// and finally whether the stream is a terminal.
+    pub fn should_colorize(self, stream: Stream) -> bool {
+        match self {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            ColorChoice::Auto => {
+                let is_terminal = match stream {
+                    Stream::Stdout => std::io::stdout().is_terminal(),
+                    Stream::Stderr => std::io::stderr().is_terminal(),
+                };
+                auto_colorize(|name| std::env::var(name).ok(), is_terminal)
+            }
+        }
+    }
+}
+
+fn auto_colorize(var: impl Fn(&str) -> Option<String>, is_terminal: bool) -> bool {
+    if var("NO_COLOR").is_some_and(|v| !v.is_empty()) {
+        return false;
+    }
+    if var("CLICOLOR_FORCE").is_some_and(|v| !v.is_empty() && v != "0") {
+        return true;
+    }
+    if var("CLICOLOR").is_some_and(|v| v == "0") {
+        return false;
+    }
+    if var("TERM").is_some_and(|v| v == "dumb") {
+        return false;
+    }
+    is_terminal
+}
+
+impl FromStr for ColorChoice {
+    type Err = ParseColorChoiceError;
+
+    /// Parses `auto`, `always` or `never`, case-insensitively, as used by `--color` flags.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "auto" => Ok(ColorChoice::Auto),
+            "always" => Ok(ColorChoice::Always),
+            "never" => Ok(ColorChoice::Never),
+            _ => Err(ParseColorChoiceError(s.to_string())),
+        }
+    }
+}
+
+/// The error returned when a string is not `auto`, `always` or `never`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseColorChoiceError(String);
+
+impl fmt::Display for ParseColorChoiceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid color choice {:?}: expected auto, always or never",
+            self.0
+        )
+    }
+}
+
+impl std::error::Error for ParseColorChoiceError {}
+
+static COLOR_CHOICE: AtomicU8 = AtomicU8::new(0);
+
+/// Cached per-stream decisions: 0 means "not decided yet", 1 disabled, 2 enabled.
+static STDOUT_ENABLED: AtomicU8 = AtomicU8::new(0);
+static STDERR_ENABLED: AtomicU8 = AtomicU8::new(0);
+
+/// Sets the process-wide color choice used by `Style::paint` and the helper functions.
+pub fn set_color_choice(choice: ColorChoice) {
+    COLOR_CHOICE.store(choice as u8, Ordering::Relaxed);
+    STDOUT_ENABLED.store(0, Ordering::Relaxed);
+    STDERR_ENABLED.store(0, Ordering::Relaxed);
+}
+
+/// Returns the process-wide color choice. Defaults to `ColorChoice::Auto`.
+pub fn color_choice() -> ColorChoice {
+    match COLOR_CHOICE.load(Ordering::Relaxed) {
+        1 => ColorChoice::Always,
+        2 => ColorChoice::Never,
+        _ => ColorChoice::Auto,
+    }
+}
+
+/// Returns true if output written to `stream` should be colored under the process-wide choice.
+///
+/// The decision is computed once per stream and cached until `set_color_choice` is called again.
+/// # Examples:
+/// ```
+/// use cli_utils::colors::{colors_enabled, set_color_choice, ColorChoice, Stream};
+/// set_color_choice(ColorChoice::Never);
+/// assert!(!colors_enabled(Stream::Stdout));
+/// assert!(!colors_enabled(Stream::Stderr));
+/// ```
+pub fn colors_enabled(stream: Stream) -> bool {
+    let cache = match stream {
+        Stream::Stdout => &STDOUT_ENABLED,
+        Stream::Stderr => &STDERR_ENABLED,
+    };
+    match cache.load(Ordering::Relaxed) {
+        1 => false,
+        2 => true,
+        _ => {
+            let enabled = color_choice().should_colorize(stream);
+            cache.store(if enabled { 2 } else { 1 }, Ordering::Relaxed);
+            enabled
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::auto_colorize;
+
+    fn env<'a>(vars: &'a [(&'a str, &'a str)]) -> impl Fn(&str) -> Option<String> + 'a {
+        move |name| {
+            vars.iter()
+                .find(|(key, _)| *key == name)
+                .map(|(_, value)| value.to_string())
+        }
+    }
+
+    #[test]
+    fn test_auto_follows_terminal_without_variables() {
+        assert!(auto_colorize(env(&[]), true));
+        assert!(!auto_colorize(env(&[]), false));
+    }
+
+    #[test]
+    fn test_no_color_wins_over_force() {
+        let vars = [("NO_COLOR", "1"), ("CLICOLOR_FORCE", "1")];
+        assert!(!auto_colorize(env(&vars), true));
+        assert!(auto_colorize(env(&[("NO_COLOR", "")]), true));
+    }
+
+    #[test]
+    fn test_clicolor_force_enables_color_when_piped() {
+        assert!(auto_colorize(env(&[("CLICOLOR_FORCE", "1")]), false));
+        assert!(!auto_colorize(env(&[("CLICOLOR_FORCE", "0")]), false));
+    }
+
+    #[test]
+    fn test_clicolor_zero_and_dumb_terminal_disable_color() {
+        assert!(!auto_colorize(env(&[("CLICOLOR", "0")]), true));
+        assert!(auto_colorize(env(&[("CLICOLOR", "1")]), true));
+        assert!(!auto_colorize(env(&[("TERM", "dumb")]), true));
+    }
+}
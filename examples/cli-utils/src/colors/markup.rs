@@ -0,0 +1,336 @@
+//! A small inline markup language for styled text, such as `[red bold]error:[/] missing file`.
+
+use super::{color_depth, colors_enabled, ColorDepth, ParseStyleError, Stream, Style, RESET};
+use std::borrow::Cow;
+use std::fmt;
+
+/// What went wrong while parsing markup.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MarkupErrorKind {
+    /// A `[` without a matching `]`.
+    UnclosedBracket,
+    /// A `[]` tag with nothing in it.
+    EmptyTag,
+    /// A tag whose contents are not a valid `Style`.
+    InvalidStyle(ParseStyleError),
+    /// A closing tag with no open tag to close.
+    UnexpectedClose,
+    /// A named closing tag that does not match the innermost open tag.
+    MismatchedClose { expected: String, found: String },
+    /// A tag that is never closed.
+    UnclosedTag(String),
+}
+
+/// The error returned by `markup` for malformed input.
+///
+/// `position` is the byte offset of the offending `[` in the input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MarkupError {
+    pub kind: MarkupErrorKind,
+    pub position: usize,
+}
+
+impl fmt::Display for MarkupError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            MarkupErrorKind::UnclosedBracket => write!(f, "missing `]` for the `[`")?,
+            MarkupErrorKind::EmptyTag => write!(f, "empty tag `[]`")?,
+            MarkupErrorKind::InvalidStyle(error) => write!(f, "{}", error)?,
+            MarkupErrorKind::UnexpectedClose => write!(f, "closing tag without an open tag")?,
+            MarkupErrorKind::MismatchedClose { expected, found } => write!(
+                f,
+                "closing tag `[/{}]` does not match the open tag `[{}]`",
+                found, expected
+            )?,
+            MarkupErrorKind::UnclosedTag(tag) => write!(f, "tag `[{}]` is never closed", tag)?,
+        }
+        write!(f, " at byte {}", self.position)
+    }
+}
+
+impl std::error::Error for MarkupError {}
+
+/// Renders markup to a string with escape codes, if colors are enabled for stdout.
+///
+/// A tag such as `[red bold]` or `[italic on blue]` holds a `Style` and applies to the text
+/// up to its closing `[/]`. A closing tag may repeat the style, as in `[/red bold]`, to catch
+/// mistakes. Tags nest, with inner colors overriding outer ones and attributes adding up.
+/// Write `\[`, `\]` and `\\` for literal brackets and backslashes; `escape_markup` does this.
+///
+/// When colors are disabled the tags are dropped and only the text is returned.
+/// # Examples:
+/// ```
+/// use cli_utils::colors::{markup, set_color_choice, ColorChoice};
+/// set_color_choice(ColorChoice::Always);
+/// assert_eq!(
+///     markup("[red bold]error:[/] \\[1\\]").unwrap(),
+///     "\x1b[1;31merror:\x1b[0m [1]"
+/// );
+/// assert!(markup("[red]unclosed").is_err());
+///
+/// set_color_choice(ColorChoice::Never);
+/// assert_eq!(markup("[red]a [underline]b[/][/]").unwrap(), "a b");
+/// ```
+pub fn markup(s: &str) -> Result<String, MarkupError> {
+    markup_to(s, Stream::Stdout)
+}
+
+/// Like `markup`, but checks whether colors are enabled for `stream`.
+pub fn markup_to(s: &str, stream: Stream) -> Result<String, MarkupError> {
+    let depth = if colors_enabled(stream) {
+        Some(color_depth())
+    } else {
+        None
+    };
+    render(s, depth)
+}
+
+/// Escapes brackets and backslashes so the string is shown literally by `markup`.
+/// # Examples:
+/// ```
+/// use cli_utils::colors::escape_markup;
+/// assert_eq!(escape_markup("vec[0]"), "vec\\[0\\]");
+/// assert_eq!(escape_markup("plain"), "plain");
+/// ```
+pub fn escape_markup(s: &str) -> Cow<'_, str> {
+    if !s.contains(['[', ']', '\\']) {
+        return Cow::Borrowed(s);
+    }
+    let mut escaped = String::with_capacity(s.len() + 4);
+    for c in s.chars() {
+        if matches!(c, '[' | ']' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    Cow::Owned(escaped)
+}
+
+struct OpenTag<'a> {
+    tag: Cow<'a, str>,
+    style: Style,
+    position: usize,
+}
+
+/// Renders markup, emitting escape codes at `depth`, or none at all when `depth` is `None`.
+fn render(s: &str, depth: Option<ColorDepth>) -> Result<String, MarkupError> {
+    let error = |kind, position| MarkupError { kind, position };
+    let mut out = String::with_capacity(s.len());
+    let mut stack: Vec<OpenTag<'_>> = Vec::new();
+    let mut chars = s.char_indices().peekable();
+
+    while let Some((position, c)) = chars.next() {
+        match c {
+            '\\' => match chars.peek() {
+                Some(&(_, next)) if matches!(next, '[' | ']' | '\\') => {
+                    out.push(next);
+                    chars.next();
+                }
+                _ => out.push('\\'),
+            },
+            '[' => {
+                let len = closing_bracket(&s[position + 1..])
+                    .ok_or_else(|| error(MarkupErrorKind::UnclosedBracket, position))?;
+                let tag = unescape(s[position + 1..position + 1 + len].trim());
+                let end = position + len + 2;
+                while chars.next_if(|&(i, _)| i < end).is_some() {}
+
+                if let Some(name) = tag.strip_prefix('/') {
+                    let open = stack
+                        .pop()
+                        .ok_or_else(|| error(MarkupErrorKind::UnexpectedClose, position))?;
+                    if !name.trim().is_empty() && !same_tag(name, &open.tag) {
+                        return Err(error(
+                            MarkupErrorKind::MismatchedClose {
+                                expected: open.tag.into_owned(),
+                                found: name.trim().to_string(),
+                            },
+                            position,
+                        ));
+                    }
+                    if let Some(depth) = depth {
+                        out.push_str(RESET);
+                        if let Some(outer) = stack.last() {
+                            out.push_str(&outer.style.prefix_for(depth));
+                        }
+                    }
+                } else {
+                    if tag.is_empty() {
+                        return Err(error(MarkupErrorKind::EmptyTag, position));
+                    }
+                    let inner: Style = tag
+                        .parse()
+                        .map_err(|e| error(MarkupErrorKind::InvalidStyle(e), position))?;
+                    let style = match stack.last() {
+                        Some(outer) => combine(outer.style, inner),
+                        None => inner,
+                    };
+                    if let Some(depth) = depth {
+                        out.push_str(&style.prefix_for(depth));
+                    }
+                    stack.push(OpenTag {
+                        tag,
+                        style,
+                        position,
+                    });
+                }
+            }
+            _ => out.push(c),
+        }
+    }
+
+    match stack.pop() {
+        Some(open) => Err(error(
+            MarkupErrorKind::UnclosedTag(open.tag.into_owned()),
+            open.position,
+        )),
+        None => Ok(out),
+    }
+}
+
+/// Returns the offset of the first `]` in `s` that is not escaped with a backslash.
+fn closing_bracket(s: &str) -> Option<usize> {
+    let mut bytes = s.bytes().enumerate();
+    while let Some((i, b)) = bytes.next() {
+        match b {
+            b'\\' => {
+                bytes.next();
+            }
+            b']' => return Some(i),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Removes the backslashes of escaped brackets and backslashes inside a tag.
+fn unescape(tag: &str) -> Cow<'_, str> {
+    if !tag.contains('\\') {
+        return Cow::Borrowed(tag);
+    }
+    let mut out = String::with_capacity(tag.len());
+    let mut chars = tag.chars().peekable();
+    while let Some(c) = chars.next() {
+        match chars.peek() {
+            Some(&next) if c == '\\' && matches!(next, '[' | ']' | '\\') => {
+                out.push(next);
+                chars.next();
+            }
+            _ => out.push(c),
+        }
+    }
+    Cow::Owned(out)
+}
+
+/// Layers `inner` on top of `outer`: colors set in `inner` win, attributes add up.
+fn combine(outer: Style, inner: Style) -> Style {
+    let mut style = outer;
+    style.foreground = inner.foreground.or(outer.foreground);
+    style.background = inner.background.or(outer.background);
+    for attr in inner.attributes.iter() {
+        style.attributes.insert(attr);
+    }
+    style
+}
+
+fn same_tag(a: &str, b: &str) -> bool {
+    a.split_whitespace().eq(b.split_whitespace())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::colors::Color;
+
+    fn render_colored(s: &str) -> Result<String, MarkupError> {
+        render(s, Some(ColorDepth::TrueColor))
+    }
+
+    #[test]
+    fn test_nested_tags_restore_outer_style() {
+        assert_eq!(
+            render_colored("[red]a[bold on blue]b[/]c[/]d").unwrap(),
+            "\x1b[31ma\x1b[1;31;44mb\x1b[0m\x1b[31mc\x1b[0md"
+        );
+    }
+
+    #[test]
+    fn test_named_close_must_match() {
+        assert!(render_colored("[red bold]x[/red  bold]").is_ok());
+        let error = render_colored("[red]x[/blue]").unwrap_err();
+        assert_eq!(
+            error.kind,
+            MarkupErrorKind::MismatchedClose {
+                expected: "red".to_string(),
+                found: "blue".to_string()
+            }
+        );
+        assert_eq!(error.position, 6);
+    }
+
+    #[test]
+    fn test_unbalanced_tags_are_reported() {
+        assert_eq!(
+            render_colored("x[/]").unwrap_err(),
+            MarkupError {
+                kind: MarkupErrorKind::UnexpectedClose,
+                position: 1
+            }
+        );
+        assert_eq!(
+            render_colored("a [green]b").unwrap_err(),
+            MarkupError {
+                kind: MarkupErrorKind::UnclosedTag("green".to_string()),
+                position: 2
+            }
+        );
+        assert_eq!(
+            render_colored("a [green").unwrap_err().kind,
+            MarkupErrorKind::UnclosedBracket
+        );
+        assert_eq!(
+            render_colored("[ ]").unwrap_err().kind,
+            MarkupErrorKind::EmptyTag
+        );
+        assert!(matches!(
+            render_colored("[sparkly]x[/]").unwrap_err().kind,
+            MarkupErrorKind::InvalidStyle(_)
+        ));
+    }
+
+    #[test]
+    fn test_escapes_round_trip() {
+        let text = r"C:\path\[1]";
+        let rendered = render(&format!("[bold]{}[/]", escape_markup(text)), None).unwrap();
+        assert_eq!(rendered, text);
+        assert_eq!(render("a ] b \\x", None).unwrap(), "a ] b \\x");
+    }
+
+    #[test]
+    fn test_escaped_bracket_does_not_close_a_tag() {
+        let error = render_colored(r"[\]]x[/]").unwrap_err();
+        assert!(matches!(error.kind, MarkupErrorKind::InvalidStyle(_)));
+        assert_eq!(error.position, 0);
+        assert_eq!(
+            render_colored(r"[red\]").unwrap_err().kind,
+            MarkupErrorKind::UnclosedBracket
+        );
+        assert_eq!(
+            render_colored(r"[red]x[/red\]]").unwrap_err().kind,
+            MarkupErrorKind::MismatchedClose {
+                expected: "red".to_string(),
+                found: "red]".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn test_combine_keeps_outer_background() {
+        let outer = Style::new().bg(Color::Blue).italic();
+        let inner = Style::new().fg(Color::Red).bold();
+        assert_eq!(
+            combine(outer, inner),
+            Style::new().bg(Color::Blue).fg(Color::Red).italic().bold()
+        );
+    }
+}
@@ -0,0 +1,108 @@
+//! Conversions between 24-bit colors and the 256- and 16-color terminal palettes.
+
+/// The xterm default values of the 16 basic colors, in SGR order.
+const BASIC: [(u8, u8, u8); 16] = [
+    (0, 0, 0),
+    (205, 0, 0),
+    (0, 205, 0),
+    (205, 205, 0),
+    (0, 0, 238),
+    (205, 0, 205),
+    (0, 205, 205),
+    (229, 229, 229),
+    (127, 127, 127),
+    (255, 0, 0),
+    (0, 255, 0),
+    (255, 255, 0),
+    (92, 92, 255),
+    (255, 0, 255),
+    (0, 255, 255),
+    (255, 255, 255),
+];
+
+/// The channel levels of the 6x6x6 color cube at indices 16..=231.
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+fn distance(a: (u8, u8, u8), b: (u8, u8, u8)) -> u32 {
+    let dr = a.0 as i32 - b.0 as i32;
+    let dg = a.1 as i32 - b.1 as i32;
+    let db = a.2 as i32 - b.2 as i32;
+    (dr * dr + dg * dg + db * db) as u32
+}
+
+fn nearest_cube_level(channel: u8) -> usize {
+    (0..CUBE_LEVELS.len())
+        .min_by_key(|&i| (CUBE_LEVELS[i] as i32 - channel as i32).abs())
+        .unwrap_or(0)
+}
+
+/// Returns the RGB value of a 256-color palette index.
+pub fn ansi256_to_rgb(index: u8) -> (u8, u8, u8) {
+    match index {
+        0..=15 => BASIC[index as usize],
+        16..=231 => {
+            let i = index - 16;
+            (
+                CUBE_LEVELS[(i / 36) as usize],
+                CUBE_LEVELS[(i / 6 % 6) as usize],
+                CUBE_LEVELS[(i % 6) as usize],
+            )
+        }
+        _ => {
+            let level = 8 + (index - 232) * 10;
+            (level, level, level)
+        }
+    }
+}
+
+/// Returns the 256-color palette index closest to an RGB value.
+///
+/// Only the color cube and the grayscale ramp are considered, since the first 16
+/// entries vary between terminals.
+pub fn rgb_to_ansi256(rgb: (u8, u8, u8)) -> u8 {
+    let (r, g, b) = (
+        nearest_cube_level(rgb.0),
+        nearest_cube_level(rgb.1),
+        nearest_cube_level(rgb.2),
+    );
+    let cube = 16 + (36 * r + 6 * g + b) as u8;
+
+    let average = (rgb.0 as u32 + rgb.1 as u32 + rgb.2 as u32) / 3;
+    let gray = if average < 8 {
+        232
+    } else {
+        232 + ((average - 8 + 5) / 10).min(23) as u8
+    };
+
+    if distance(rgb, ansi256_to_rgb(gray)) < distance(rgb, ansi256_to_rgb(cube)) {
+        gray
+    } else {
+        cube
+    }
+}
+
+/// Returns the index (0..16) of the basic color closest to an RGB value.
+pub fn rgb_to_ansi16(rgb: (u8, u8, u8)) -> u8 {
+    (0..BASIC.len())
+        .min_by_key(|&i| distance(rgb, BASIC[i]))
+        .unwrap_or(0) as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_palette_round_trips_through_cube_and_grayscale() {
+        for index in 16..=255u8 {
+            assert_eq!(rgb_to_ansi256(ansi256_to_rgb(index)), index);
+        }
+    }
+
+    #[test]
+    fn test_rgb_to_ansi16_picks_nearest_basic_color() {
+        assert_eq!(rgb_to_ansi16((250, 10, 10)), 9);
+        assert_eq!(rgb_to_ansi16((10, 10, 10)), 0);
+        assert_eq!(rgb_to_ansi16((130, 130, 130)), 8);
+    }
+}
@@ -0,0 +1,377 @@
+//! Semantic styles, such as "error" or "path", grouped into named themes.
+
+use super::{Color, Style};
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+
+/// A semantic role that text can play in a CLI's output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Role {
+    Error,
+    Warning,
+    Success,
+    Info,
+    Path,
+    Muted,
+    Emphasis,
+}
+
+impl Role {
+    pub const ALL: [Role; 7] = [
+        Role::Error,
+        Role::Warning,
+        Role::Success,
+        Role::Info,
+        Role::Path,
+        Role::Muted,
+        Role::Emphasis,
+    ];
+
+    /// Looks a role up by its theme file key, case-insensitively.
+    pub fn from_name(name: &str) -> Option<Role> {
+        Role::ALL
+            .into_iter()
+            .find(|role| role.name().eq_ignore_ascii_case(name))
+    }
+
+    /// Returns the key used for this role in theme files.
+    pub fn name(self) -> &'static str {
+        match self {
+            Role::Error => "error",
+            Role::Warning => "warning",
+            Role::Success => "success",
+            Role::Info => "info",
+            Role::Path => "path",
+            Role::Muted => "muted",
+            Role::Emphasis => "emphasis",
+        }
+    }
+}
+
+/// A named mapping from semantic roles to styles.
+///
+/// Themes can be picked from the built-in ones or loaded from a file, so users can
+/// restyle a CLI without recompiling it.
+/// # Examples:
+/// ```
+/// use cli_utils::colors::{set_color_choice, ColorChoice, Theme};
+/// set_color_choice(ColorChoice::Never);
+/// let theme = Theme::dark();
+/// assert_eq!(theme.error("failed"), "failed");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Theme {
+    pub name: String,
+    styles: [Style; 7],
+}
+
+impl Theme {
+    /// Creates a theme in which every role is unstyled.
+    pub fn plain(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            styles: [Style::new(); 7],
+        }
+    }
+
+    /// A theme for terminals with a dark background.
+    pub fn dark() -> Self {
+        Self::plain("dark")
+            .with(Role::Error, Style::new().bold().fg(Color::BrightRed))
+            .with(Role::Warning, Style::new().fg(Color::BrightYellow))
+            .with(Role::Success, Style::new().fg(Color::BrightGreen))
+            .with(Role::Info, Style::new().fg(Color::BrightCyan))
+            .with(Role::Path, Style::new().underline().fg(Color::BrightBlue))
+            .with(Role::Muted, Style::new().fg(Color::BrightBlack))
+            .with(Role::Emphasis, Style::new().bold())
+    }
+
+    /// A theme for terminals with a light background.
+    pub fn light() -> Self {
+        Self::plain("light")
+            .with(Role::Error, Style::new().bold().fg(Color::Red))
+            .with(Role::Warning, Style::new().fg(Color::Ansi256(130)))
+            .with(Role::Success, Style::new().fg(Color::Green))
+            .with(Role::Info, Style::new().fg(Color::Blue))
+            .with(Role::Path, Style::new().underline().fg(Color::Blue))
+            .with(Role::Muted, Style::new().fg(Color::Ansi256(244)))
+            .with(Role::Emphasis, Style::new().bold())
+    }
+
+    /// A theme that relies on backgrounds and attributes rather than hue alone.
+    pub fn high_contrast() -> Self {
+        let on = |fg, bg| Style::new().bold().fg(fg).bg(bg);
+        Self::plain("high-contrast")
+            .with(Role::Error, on(Color::BrightWhite, Color::Red))
+            .with(Role::Warning, on(Color::Black, Color::BrightYellow))
+            .with(Role::Success, on(Color::Black, Color::BrightGreen))
+            .with(Role::Info, on(Color::BrightWhite, Color::Blue))
+            .with(
+                Role::Path,
+                Style::new().bold().underline().fg(Color::BrightWhite),
+            )
+            .with(Role::Muted, Style::new().fg(Color::White))
+            .with(Role::Emphasis, Style::new().bold().underline())
+    }
+
+    /// Returns the built-in theme called `name`: `dark`, `light` or `high-contrast`.
+    pub fn builtin(name: &str) -> Option<Theme> {
+        match name.to_ascii_lowercase().replace('_', "-").as_str() {
+            "dark" => Some(Self::dark()),
+            "light" => Some(Self::light()),
+            "high-contrast" => Some(Self::high_contrast()),
+            _ => None,
+        }
+    }
+
+    /// Returns the theme with `role` set to `style`.
+    pub fn with(mut self, role: Role, style: Style) -> Self {
+        self.set(role, style);
+        self
+    }
+
+    pub fn set(&mut self, role: Role, style: Style) {
+        self.styles[role as usize] = style;
+    }
+
+    pub fn style(&self, role: Role) -> Style {
+        self.styles[role as usize]
+    }
+
+    /// Paints the string with the style of `role`, if colors are enabled for stdout.
+    pub fn paint(&self, role: Role, s: &str) -> String {
+        self.style(role).paint(s)
+    }
+
+    pub fn error(&self, s: &str) -> String {
+        self.paint(Role::Error, s)
+    }
+
+    pub fn warning(&self, s: &str) -> String {
+        self.paint(Role::Warning, s)
+    }
+
+    pub fn success(&self, s: &str) -> String {
+        self.paint(Role::Success, s)
+    }
+
+    pub fn info(&self, s: &str) -> String {
+        self.paint(Role::Info, s)
+    }
+
+    pub fn path(&self, s: &str) -> String {
+        self.paint(Role::Path, s)
+    }
+
+    pub fn muted(&self, s: &str) -> String {
+        self.paint(Role::Muted, s)
+    }
+
+    pub fn emphasis(&self, s: &str) -> String {
+        self.paint(Role::Emphasis, s)
+    }
+
+    /// Reads a theme file; see `Theme::from_str` for the format.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Theme, ThemeError> {
+        std::fs::read_to_string(path)?.parse()
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::dark()
+    }
+}
+
+impl FromStr for Theme {
+    type Err = ThemeError;
+
+    /// Parses a theme from `key = value` lines.
+    ///
+    /// Keys are role names, plus `name` and `base`. `base` names the built-in theme that
+    /// unlisted roles are taken from and defaults to `dark`. Values are styles such as
+    /// `bold red on #202020` and may be wrapped in double quotes, as in TOML. Lines starting
+    /// with `#` are comments, as is the rest of a line after a `#` that follows a space and
+    /// does not start a hex color. `[section]` headers are ignored.
+    /// # Examples:
+    /// ```
+    /// use cli_utils::colors::{Color, Role, Style, Theme};
+    /// let theme: Theme = "
+    ///     ## our brand colors
+    ///     [theme]
+    ///     name = \"acme\"
+    ///     base = light
+    ///     error = \"bold #d70000\"
+    ///     path = underline cyan
+    /// ".parse().unwrap();
+    /// assert_eq!(theme.name, "acme");
+    /// assert_eq!(theme.style(Role::Error), Style::new().bold().fg(Color::Rgb(215, 0, 0)));
+    /// assert_eq!(theme.style(Role::Success), Theme::light().style(Role::Success));
+    /// ```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut name = None;
+        let mut base = None;
+        let mut styles = Vec::new();
+
+        for (index, line) in s.lines().enumerate() {
+            let line_number = index + 1;
+            let error = |message: String| ThemeError::Parse {
+                line: line_number,
+                message,
+            };
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') || line.starts_with('[') {
+                continue;
+            }
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| error(format!("expected `key = value`, found {:?}", line)))?;
+            let key = key.trim();
+            let value = unquote(value.trim()).map_err(error)?;
+            match key {
+                "name" => name = Some(value.to_string()),
+                "base" => {
+                    base = Some(
+                        Theme::builtin(value)
+                            .ok_or_else(|| error(format!("unknown base theme {:?}", value)))?,
+                    )
+                }
+                _ => {
+                    let role = Role::from_name(key)
+                        .ok_or_else(|| error(format!("unknown role {:?}", key)))?;
+                    let style = value.parse::<Style>().map_err(|e| error(e.to_string()))?;
+                    styles.push((role, style));
+                }
+            }
+        }
+
+        let mut theme = base.unwrap_or_default();
+        if let Some(name) = name {
+            theme.name = name;
+        }
+        for (role, style) in styles {
+            theme.set(role, style);
+        }
+        Ok(theme)
+    }
+}
+
+/// Strips the double quotes around a value, along with an optional trailing comment.
+fn unquote(value: &str) -> Result<&str, String> {
+    let Some(rest) = value.strip_prefix('"') else {
+        return Ok(strip_comment(value));
+    };
+    let (inner, after) = rest
+        .split_once('"')
+        .ok_or_else(|| format!("missing closing quote in {}", value))?;
+    let after = after.trim();
+    if !after.is_empty() && !after.starts_with('#') {
+        return Err(format!("unexpected {:?} after quoted value", after));
+    }
+    Ok(inner)
+}
+
+/// Removes a trailing ` # comment` from an unquoted value. A `#` that starts a hex color
+/// such as `#d70000` is part of the value.
+fn strip_comment(value: &str) -> &str {
+    let is_hex_color = |word: &str| {
+        let digits = &word[1..];
+        matches!(digits.len(), 3 | 6) && digits.chars().all(|c| c.is_ascii_hexdigit())
+    };
+    for (i, c) in value.char_indices() {
+        let after_space = i == 0 || value[..i].ends_with(char::is_whitespace);
+        if c == '#' && after_space {
+            let word = value[i..].split_whitespace().next().unwrap_or("#");
+            if !is_hex_color(word) {
+                return value[..i].trim_end();
+            }
+        }
+    }
+    value
+}
+
+/// The error returned when a theme cannot be read or parsed.
+#[derive(Debug)]
+pub enum ThemeError {
+    Io(std::io::Error),
+    /// A malformed line; `line` is 1-based.
+    Parse {
+        line: usize,
+        message: String,
+    },
+}
+
+impl fmt::Display for ThemeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ThemeError::Io(error) => write!(f, "could not read theme: {}", error),
+            ThemeError::Parse { line, message } => {
+                write!(f, "invalid theme on line {}: {}", line, message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ThemeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ThemeError::Io(error) => Some(error),
+            ThemeError::Parse { .. } => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for ThemeError {
+    fn from(error: std::io::Error) -> Self {
+        ThemeError::Io(error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_themes_style_every_role() {
+        for theme in [Theme::dark(), Theme::light(), Theme::high_contrast()] {
+            for role in Role::ALL {
+                assert!(!theme.style(role).is_plain(), "{} {:?}", theme.name, role);
+            }
+            assert_eq!(Theme::builtin(&theme.name), Some(theme));
+        }
+    }
+
+    #[test]
+    fn test_parse_errors_report_line_numbers() {
+        let error = "name = x\nfoo = red".parse::<Theme>().unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "invalid theme on line 2: unknown role \"foo\""
+        );
+
+        let error = "error = sparkly".parse::<Theme>().unwrap_err();
+        assert!(matches!(error, ThemeError::Parse { line: 1, .. }));
+
+        let error = "\n\nerror = \"red".parse::<Theme>().unwrap_err();
+        assert!(matches!(error, ThemeError::Parse { line: 3, .. }));
+
+        assert!("just words".parse::<Theme>().is_err());
+        assert!("base = neon".parse::<Theme>().is_err());
+    }
+
+    #[test]
+    fn test_unquote_allows_hex_and_trailing_comments() {
+        assert_eq!(unquote("#ff0000"), Ok("#ff0000"));
+        assert_eq!(unquote("\"#ff0000\" # brand red"), Ok("#ff0000"));
+        assert!(unquote("\"red\" bold").is_err());
+        assert_eq!(unquote("bold red # errors"), Ok("bold red"));
+        assert_eq!(unquote("bold #d70000 #brand"), Ok("bold #d70000"));
+        assert_eq!(unquote("cyan#1"), Ok("cyan#1"));
+        let theme: Theme = "error = underline #fff on blue   # links".parse().unwrap();
+        assert_eq!(
+            theme.style(Role::Error),
+            "underline #fff on blue".parse::<Style>().unwrap()
+        );
+    }
+}
@@ -0,0 +1,228 @@
+//! Completing the word under the cursor with Tab.
+
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+#[cfg(any(unix, test))]
+use unicode_width::UnicodeWidthStr;
+
+/// A possible completion: the text to put in place of part of the line.
+/// # Examples:
+/// ```
+/// use cli_utils::Candidate;
+/// let candidate = Candidate::new(4..6, "src/").display("src/ (directory)");
+/// assert_eq!(candidate.replacement, "src/");
+/// assert_eq!(candidate.display, "src/ (directory)");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Candidate {
+    /// The byte range of the line that the replacement takes the place of.
+    pub range: Range<usize>,
+    pub replacement: String,
+    /// How the candidate is shown in the list of candidates.
+    pub display: String,
+}
+
+impl Candidate {
+    /// Creates a candidate that is shown as its replacement.
+    pub fn new(range: Range<usize>, replacement: impl Into<String>) -> Self {
+        let replacement = replacement.into();
+        Self {
+            range,
+            display: replacement.clone(),
+            replacement,
+        }
+    }
+
+    /// Shows the candidate as `display` in the list instead of as its replacement.
+    pub fn display(mut self, display: impl Into<String>) -> Self {
+        self.display = display.into();
+        self
+    }
+}
+
+/// Suggests completions for a line being edited.
+///
+/// Closures taking the line and the cursor are completers too, which is usually enough
+/// for fixed words such as subcommand names.
+/// # Examples:
+/// ```
+/// use cli_utils::{Candidate, Completer};
+/// let subcommands = |line: &str, cursor: usize| {
+///     let start = line[..cursor].rfind(' ').map_or(0, |i| i + 1);
+///     let word = &line[start..cursor];
+///     ["build", "bench", "test"]
+///         .iter()
+///         .filter(|name| name.starts_with(word))
+///         .map(|name| Candidate::new(start..cursor, *name))
+///         .collect::<Vec<_>>()
+/// };
+/// let candidates = subcommands.complete("cargo b", 7);
+/// assert_eq!(candidates[0].replacement, "build");
+/// assert_eq!(candidates[1].replacement, "bench");
+/// ```
+pub trait Completer {
+    /// Returns the candidates for `line` with the cursor at byte offset `cursor`.
+    fn complete(&self, line: &str, cursor: usize) -> Vec<Candidate>;
+}
+
+impl<F: Fn(&str, usize) -> Vec<Candidate>> Completer for F {
+    fn complete(&self, line: &str, cursor: usize) -> Vec<Candidate> {
+        self(line, cursor)
+    }
+}
+
+/// Completes the whitespace-separated word before the cursor as a file system path.
+///
+/// Directories are completed with a trailing `/`, hidden files only when the word starts
+/// with a dot, and a leading `~/` stands for the home directory. Names are not quoted, so
+/// names with spaces in them are completed as they are.
+/// # Examples:
+/// ```no_run
+/// use cli_utils::{Completer, PathCompleter};
+/// for candidate in PathCompleter::new().complete("cat src/ma", 10) {
+///     println!("{}", candidate.replacement);
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct PathCompleter {
+    base: Option<PathBuf>,
+}
+
+impl PathCompleter {
+    /// Creates a completer for paths relative to the current directory.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Resolves relative paths against `dir` instead of the current directory.
+    pub fn base_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.base = Some(dir.into());
+        self
+    }
+
+    fn resolve(&self, dir: &str) -> Option<PathBuf> {
+        if let Some(rest) = dir.strip_prefix("~/") {
+            return std::env::var_os("HOME").map(|home| Path::new(&home).join(rest));
+        }
+        let base = self.base.clone().unwrap_or_else(|| PathBuf::from("."));
+        Some(if dir.is_empty() { base } else { base.join(dir) })
+    }
+}
+
+impl Completer for PathCompleter {
+    fn complete(&self, line: &str, cursor: usize) -> Vec<Candidate> {
+        let start = line[..cursor]
+            .char_indices()
+            .rev()
+            .find(|(_, c)| c.is_whitespace())
+            .map_or(0, |(i, c)| i + c.len_utf8());
+        let word = &line[start..cursor];
+        let (dir, prefix) = word.split_at(word.rfind('/').map_or(0, |i| i + 1));
+        let Some(entries) = self.resolve(dir).and_then(|path| path.read_dir().ok()) else {
+            return Vec::new();
+        };
+        let mut candidates: Vec<Candidate> = entries
+            .filter_map(Result::ok)
+            .filter_map(|entry| {
+                let name = entry.file_name().into_string().ok()?;
+                if !name.starts_with(prefix) || (name.starts_with('.') && !prefix.starts_with('.'))
+                {
+                    return None;
+                }
+                let slash = if entry.path().is_dir() { "/" } else { "" };
+                Some(
+                    Candidate::new(start..cursor, format!("{}{}{}", dir, name, slash))
+                        .display(format!("{}{}", name, slash)),
+                )
+            })
+            .collect();
+        candidates.sort_by(|a, b| a.replacement.cmp(&b.replacement));
+        candidates
+    }
+}
+
+/// Returns the longest prefix that all `words` start with.
+#[cfg(any(unix, test))]
+pub(crate) fn common_prefix<'a>(words: impl IntoIterator<Item = &'a str>) -> &'a str {
+    let mut words = words.into_iter();
+    let Some(mut prefix) = words.next() else {
+        return "";
+    };
+    for word in words {
+        let len = prefix
+            .char_indices()
+            .zip(word.chars())
+            .find(|((_, a), b)| a != b)
+            .map_or(prefix.len().min(word.len()), |((i, _), _)| i);
+        prefix = &prefix[..len];
+    }
+    prefix
+}
+
+/// Lays `items` out in columns that fit in `width`, filling each row from left to right.
+#[cfg(any(unix, test))]
+pub(crate) fn format_columns(items: &[&str], width: usize) -> String {
+    let column = items.iter().map(|item| item.width()).max().unwrap_or(0) + 2;
+    let per_row = (width / column).max(1);
+    let mut out = String::new();
+    for row in items.chunks(per_row) {
+        for (i, item) in row.iter().enumerate() {
+            out.push_str(item);
+            if i + 1 < row.len() {
+                out.push_str(&" ".repeat(column - item.width()));
+            }
+        }
+        out.push('\n');
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_path_completer_lists_matching_entries() {
+        let dir = std::env::temp_dir().join(format!("cli-utils-complete-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("src/bin")).unwrap();
+        for file in ["src/main.rs", "src/macros.rs", "src/.hidden", "Cargo.toml"] {
+            fs::write(dir.join(file), "").unwrap();
+        }
+        let completer = PathCompleter::new().base_dir(&dir);
+        let complete = |line: &str| -> Vec<(Range<usize>, String, String)> {
+            completer
+                .complete(line, line.len())
+                .into_iter()
+                .map(|c| (c.range, c.replacement, c.display))
+                .collect()
+        };
+
+        assert_eq!(
+            complete("cat src/ma"),
+            [
+                (4..10, "src/macros.rs".to_string(), "macros.rs".to_string()),
+                (4..10, "src/main.rs".to_string(), "main.rs".to_string()),
+            ]
+        );
+        assert_eq!(complete("ls s")[0].1, "src/");
+        assert_eq!(complete("ls src/")[0].1, "src/bin/");
+        assert_eq!(complete("ls src/.")[0].1, "src/.hidden");
+        assert!(complete("ls missing/").is_empty());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_common_prefix_and_columns() {
+        assert_eq!(common_prefix(["src/main.rs", "src/macros.rs"]), "src/ma");
+        assert_eq!(common_prefix(["élan", "éloge"]), "él");
+        assert_eq!(common_prefix(["abc", "ab"]), "ab");
+        assert_eq!(common_prefix([]), "");
+        assert_eq!(
+            format_columns(&["one", "two", "three", "four"], 16),
+            "one    two\nthree  four\n"
+        );
+        assert_eq!(format_columns(&["wide"], 2), "wide\n");
+    }
+}
@@ -5,19 +5,259 @@
 //! let config = Logging::new();
 //! ```
 //! 
+//! Installing a logger and writing records with the logging macros:
+//! ```
+//! use cli_utils::config::{Logging, LogLevel, LogOutput};
+//! use cli_utils::{debug, info};
+//! let config = Logging{ enabled: true, level: LogLevel::Info, destination: LogOutput::Stderr, ..Logging::new() };
+//! config.init().unwrap();
+//! info!("listening on port {}", 8080);
+//! debug!("this record is filtered out");
+//! ```
+
+use crate::colors::ColorChoice;
+use std::fmt;
+use std::str::FromStr;
+
+#[cfg(feature = "log")]
+mod facade;
+mod filter;
+mod format;
+mod journald;
+mod logger;
+mod nonblocking;
+mod rotate;
+mod syslog;
+mod time;
+
+#[cfg(feature = "log")]
+pub use facade::InitError;
+pub use filter::{Directive, Directives, ParseDirectiveError};
+pub use format::{Field, FieldValue, LogFormat, ParseLogFormatError};
+pub use logger::{flush, log, log_enabled, log_with};
+pub use nonblocking::{LogGuard, NonBlocking, Overflow, ParseOverflowError};
+pub use rotate::{ParseRotationError, Rotation, RotationPolicy, RotationSuffix};
+
+/// How severe a log record is. Levels are ordered, so `Debug < Info < Warn < Error`.
+/// # Examples:
+/// ```
+/// use cli_utils::config::LogLevel;
+/// let level: LogLevel = "WARNING".parse().unwrap();
+/// assert_eq!(level, LogLevel::Warn);
+/// assert!(LogLevel::Debug < level);
+/// assert_eq!(level.to_string(), "warn");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
 pub enum LogLevel {
     Debug,
+    #[default]
     Info,
     Warn,
     Error,
 }
 
+impl LogLevel {
+    /// Returns the upper-case name written in front of each record.
+    pub fn label(self) -> &'static str {
+        match self {
+            LogLevel::Debug => "DEBUG",
+            LogLevel::Info => "INFO",
+            LogLevel::Warn => "WARN",
+            LogLevel::Error => "ERROR",
+        }
+    }
+
+    /// Returns the syslog severity of the level, which the journal uses as its priority:
+    /// 7 (debug), 6 (info), 4 (warning) or 3 (error).
+    pub fn priority(self) -> u8 {
+        match self {
+            LogLevel::Debug => 7,
+            LogLevel::Info => 6,
+            LogLevel::Warn => 4,
+            LogLevel::Error => 3,
+        }
+    }
+}
+
+impl fmt::Display for LogLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(&self.label().to_ascii_lowercase())
+    }
+}
+
+impl FromStr for LogLevel {
+    type Err = ParseLogLevelError;
+
+    /// Parses `debug`, `info`, `warn` (or `warning`) and `error`, case-insensitively.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "debug" => Ok(LogLevel::Debug),
+            "info" => Ok(LogLevel::Info),
+            "warn" | "warning" => Ok(LogLevel::Warn),
+            "error" => Ok(LogLevel::Error),
+            _ => Err(ParseLogLevelError(s.to_string())),
+        }
+    }
+}
+
+/// The error returned when a string is not a log level.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseLogLevelError(String);
+
+impl fmt::Display for ParseLogLevelError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid log level {:?}: expected one of debug, info, warn, error",
+            self.0
+        )
+    }
+}
+
+impl std::error::Error for ParseLogLevelError {}
+
+/// Where log records are written.
+/// # Examples:
+/// ```
+/// use cli_utils::config::LogOutput;
+/// assert_eq!("STDERR".parse::<LogOutput>().unwrap(), LogOutput::Stderr);
+/// assert_eq!("/var/log/app.log".parse::<LogOutput>().unwrap(), LogOutput::File("/var/log/app.log".to_string()));
+/// assert_eq!("syslog".parse::<LogOutput>().unwrap(), LogOutput::Syslog("/dev/log".to_string()));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
 pub enum LogOutput {
+    #[default]
     Stdout,
     Stderr,
     File(String),
+    /// RFC 5424 messages sent to the syslog datagram socket at the given path, usually
+    /// `/dev/log`. Only available on Unix.
+    Syslog(String),
+    /// Entries sent with the native journal protocol to the socket at the given path,
+    /// usually `/run/systemd/journal/socket`. Only available on Unix.
+    Journald(String),
+}
+
+impl LogOutput {
+    /// Returns `LogOutput::Syslog` for the local syslog socket, `/dev/log`.
+    pub fn syslog() -> Self {
+        LogOutput::Syslog(syslog::DEFAULT_SOCKET.to_string())
+    }
+
+    /// Returns `LogOutput::Journald` for the local journal socket, `/run/systemd/journal/socket`.
+    pub fn journald() -> Self {
+        LogOutput::Journald(journald::DEFAULT_SOCKET.to_string())
+    }
+}
+
+impl fmt::Display for LogOutput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LogOutput::Stdout => f.write_str("stdout"),
+            LogOutput::Stderr => f.write_str("stderr"),
+            LogOutput::File(path) => f.write_str(path),
+            LogOutput::Syslog(path) if path == syslog::DEFAULT_SOCKET => f.write_str("syslog"),
+            LogOutput::Syslog(path) => write!(f, "syslog:{}", path),
+            LogOutput::Journald(path) if path == journald::DEFAULT_SOCKET => {
+                f.write_str("journald")
+            }
+            LogOutput::Journald(path) => write!(f, "journald:{}", path),
+        }
+    }
+}
+
+impl FromStr for LogOutput {
+    type Err = ParseLogOutputError;
+
+    /// Parses `stdout` (or `-`), `stderr`, `syslog` and `journald` case-insensitively;
+    /// `syslog:PATH` and `journald:PATH` name another socket. Anything else is a file path.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.is_empty() {
+            return Err(ParseLogOutputError);
+        }
+        let socket = |scheme: &str| {
+            s.get(..scheme.len() + 1)
+                .filter(|prefix| prefix.eq_ignore_ascii_case(&format!("{}:", scheme)))
+                .map(|prefix| s[prefix.len()..].to_string())
+        };
+        match (socket("syslog"), socket("journald")) {
+            (Some(path), _) | (_, Some(path)) if path.is_empty() => {
+                return Err(ParseLogOutputError)
+            }
+            (Some(path), _) => return Ok(LogOutput::Syslog(path)),
+            (_, Some(path)) => return Ok(LogOutput::Journald(path)),
+            (None, None) => {}
+        }
+        Ok(match s.to_ascii_lowercase().as_str() {
+            "stdout" | "-" => LogOutput::Stdout,
+            "stderr" => LogOutput::Stderr,
+            "syslog" => LogOutput::syslog(),
+            "journald" => LogOutput::journald(),
+            _ => LogOutput::File(s.to_string()),
+        })
+    }
+}
+
+/// The error returned when a log output is empty.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseLogOutputError;
+
+impl fmt::Display for ParseLogOutputError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("invalid log output: expected stdout, stderr, syslog, journald or a file path")
+    }
+}
+
+impl std::error::Error for ParseLogOutputError {}
+
+/// One destination of a logger with several, see `Logging::sinks`.
+/// # Examples:
+/// ```
+/// use cli_utils::config::{LogFormat, LogLevel, LogOutput, Sink};
+/// let sink = Sink{ level: LogLevel::Debug, format: LogFormat::Json, ..Sink::new(LogOutput::File("debug.log".to_string())) };
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sink {
+    pub output: LogOutput,
+    /// The least severe level written to this sink, on top of the filters of `Logging`.
+    pub level: LogLevel,
+    pub format: LogFormat,
+    /// Whether the level of `LogFormat::Text` records is colored. `Auto` colors stdout and
+    /// stderr when `colors::colors_enabled` allows it, and never colors files.
+    pub color: ColorChoice,
+    /// How a `LogOutput::File` output is rotated.
+    pub rotation: Rotation,
+}
+
+impl Sink {
+    /// Creates a sink writing every record `Logging` lets through to `output` as text.
+    pub fn new(output: LogOutput) -> Self {
+        Self {
+            output,
+            level: LogLevel::Debug,
+            format: LogFormat::Text,
+            color: ColorChoice::Auto,
+            rotation: Rotation::new(),
+        }
+    }
+}
+
+/// The error returned by `Logging::from_env` when a variable holds an invalid value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EnvError {
+    pub var: String,
+    pub message: String,
 }
 
+impl fmt::Display for EnvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.var, self.message)
+    }
+}
+
+impl std::error::Error for EnvError {}
+
 /// This struct contains configuration options for the application.
 /// # Examples:
 /// ```
@@ -28,12 +268,42 @@ pub enum LogOutput {
 /// Creating a new instance of the Logging struct:
 /// ```
 /// use cli_utils::config::{Logging, LogLevel, LogOutput};
-/// let config = Logging{ enabled: true, level: LogLevel::Info, destination: LogOutput::Stdout };
+/// let config = Logging{ enabled: true, level: LogLevel::Info, destination: LogOutput::Stdout, ..Logging::new() };
+/// ```
+///
+/// Per-target levels, like `RUST_LOG`:
+/// ```
+/// use cli_utils::config::{Logging, LogLevel};
+/// let config = Logging{ enabled: true, ..Logging::new() }
+///     .with_directives("warn,myapp::sync=debug,hyper=off")
+///     .unwrap();
+/// assert!(config.allows(LogLevel::Debug, "myapp::sync"));
+/// assert!(!config.allows(LogLevel::Info, "myapp::db"));
+/// assert!(!config.allows(LogLevel::Error, "hyper::client"));
+/// ```
+///
+/// Human-readable records on stderr and, with debug records too, JSON in a file:
+/// ```
+/// use cli_utils::config::{Logging, LogFormat, LogLevel, LogOutput, Sink};
+/// let config = Logging{ enabled: true, level: LogLevel::Debug, ..Logging::new() }
+///     .with_sink(Sink{ level: LogLevel::Info, ..Sink::new(LogOutput::Stderr) })
+///     .with_sink(Sink{ format: LogFormat::Json, ..Sink::new(LogOutput::File("app.log".to_string())) });
+/// assert!(config.allows(LogLevel::Debug, "myapp"));
 /// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Logging {
     pub enabled: bool,
     pub level: LogLevel,
     pub destination: LogOutput,   
+    /// Per-target levels that take precedence over `level`.
+    pub directives: Directives,
+    pub format: LogFormat,
+    /// How a `LogOutput::File` destination is rotated.
+    pub rotation: Rotation,
+    /// Destinations every record is written to, each with its own level, format and colors.
+    /// When empty, records go to `destination` in `format`; otherwise `destination`,
+    /// `format` and `rotation` are not used.
+    pub sinks: Vec<Sink>,
 }
 
 impl Logging {
@@ -42,6 +312,319 @@ impl Logging {
             enabled: false,
             level: LogLevel::Info,
             destination: LogOutput::Stdout,
+            directives: Directives::new(),
+            format: LogFormat::Text,
+            rotation: Rotation::new(),
+            sinks: Vec::new(),
+        }
+    }
+
+    /// Returns the configuration with `sink` added to `sinks`.
+    pub fn with_sink(mut self, sink: Sink) -> Self {
+        self.sinks.push(sink);
+        self
+    }
+
+    /// Returns `sinks`, or the single sink described by `destination`, `format` and
+    /// `rotation` if there are none.
+    fn effective_sinks(&self) -> Vec<Sink> {
+        if !self.sinks.is_empty() {
+            return self.sinks.clone();
+        }
+        vec![Sink {
+            level: LogLevel::Debug,
+            format: self.format,
+            color: ColorChoice::Never,
+            rotation: self.rotation,
+            ..Sink::new(self.destination.clone())
+        }]
+    }
+
+    /// Returns the configuration with `directives`, such as `info,myapp::sync=debug,hyper=off`,
+    /// parsed into per-target levels.
+    pub fn with_directives(mut self, directives: &str) -> Result<Self, ParseDirectiveError> {
+        self.directives = directives.parse()?;
+        Ok(self)
+    }
+
+    /// Returns true if a record at `level` from `target` passes this configuration's filters.
+    ///
+    /// The directive with the longest target matching `target` decides; without one,
+    /// the record must be at `level` or above. With `sinks`, at least one of them must
+    /// also take records at `level`.
+    pub fn allows(&self, level: LogLevel, target: &str) -> bool {
+        if !self.enabled || self.sink_level().is_some_and(|min| level < min) {
+            return false;
+        }
+        match self.directives.level_for(target) {
+            Some(Some(min)) => level >= min,
+            Some(None) => false,
+            None => level >= self.level,
+        }
+    }
+
+    /// Returns the most verbose level any target can log at, or `None` if everything is off.
+    pub fn max_level(&self) -> Option<LogLevel> {
+        if !self.enabled {
+            return None;
+        }
+        let fallback = match self.directives.level_for("") {
+            Some(level) => level,
+            None => Some(self.level),
+        };
+        let level = self
+            .directives
+            .iter()
+            .filter_map(|rule| rule.level)
+            .chain(fallback)
+            .min()?;
+        Some(level.max(self.sink_level().unwrap_or(level)))
+    }
+
+    /// Returns the least severe level any of `sinks` takes, or `None` without sinks.
+    fn sink_level(&self) -> Option<LogLevel> {
+        self.sinks.iter().map(|sink| sink.level).min()
+    }
+
+    /// Builds a configuration from `PREFIX_LOG`, `PREFIX_LOG_LEVEL` and `PREFIX_LOG_FILE`,
+    /// where `PREFIX` is `prefix` upper-cased with dashes turned into underscores.
+    ///
+    /// * `PREFIX_LOG` turns logging on or off (`1`/`true`/`on`/`yes`, `0`/`false`/`off`/`no`),
+    ///   or turns it on at a level, as in `MYAPP_LOG=debug`, or with per-target directives,
+    ///   as in `MYAPP_LOG=info,myapp::sync=debug`.
+    /// * `PREFIX_LOG_LEVEL` sets the level.
+    /// * `PREFIX_LOG_FILE` sets the destination: `stdout`, `stderr`, `syslog`, `journald`
+    ///   or a file path.
+    ///
+    /// Setting either of the last two also turns logging on, unless `PREFIX_LOG` turns it off.
+    /// Unset variables keep the values of `Logging::new()`.
+    /// # Examples:
+    /// ```
+    /// use cli_utils::config::{Logging, LogLevel, LogOutput};
+    /// std::env::set_var("DOCTEST_LOG", "debug");
+    /// std::env::set_var("DOCTEST_LOG_FILE", "stderr");
+    /// let config = Logging::from_env("doctest").unwrap();
+    /// assert!(config.enabled);
+    /// assert_eq!(config.level, LogLevel::Debug);
+    /// assert_eq!(config.destination, LogOutput::Stderr);
+    /// ```
+    /// # Errors:
+    /// Returns an error naming the variable if one of them holds an invalid value.
+    pub fn from_env(prefix: &str) -> Result<Self, EnvError> {
+        Self::from_vars(prefix, |name| std::env::var(name).ok())
+    }
+
+    fn from_vars(prefix: &str, var: impl Fn(&str) -> Option<String>) -> Result<Self, EnvError> {
+        let prefix = prefix.to_ascii_uppercase().replace('-', "_");
+        let name = |suffix: &str| {
+            if prefix.is_empty() {
+                suffix.to_string()
+            } else {
+                format!("{}_{}", prefix, suffix)
+            }
+        };
+        let invalid = |var: String, message: String| EnvError { var, message };
+        let mut config = Self::new();
+        let mut switch = None;
+
+        let log_var = name("LOG");
+        if let Some(value) = var(&log_var) {
+            match value.trim().to_ascii_lowercase().as_str() {
+                "1" | "true" | "on" | "yes" => switch = Some(true),
+                "0" | "false" | "off" | "no" | "" => switch = Some(false),
+                _ if value.contains([',', '=']) => {
+                    config.directives = value.parse().map_err(|e: ParseDirectiveError| {
+                        invalid(log_var.clone(), e.to_string())
+                    })?;
+                    switch = Some(true);
+                }
+                _ => {
+                    config.level = value.parse().map_err(|e: ParseLogLevelError| {
+                        invalid(
+                            log_var.clone(),
+                            format!("expected a boolean or a level, {}", e),
+                        )
+                    })?;
+                    switch = Some(true);
+                }
+            }
+        }
+
+        let level_var = name("LOG_LEVEL");
+        if let Some(value) = var(&level_var) {
+            config.level = value
+                .parse()
+                .map_err(|e: ParseLogLevelError| invalid(level_var, e.to_string()))?;
+            switch = switch.or(Some(true));
         }
+
+        let file_var = name("LOG_FILE");
+        if let Some(value) = var(&file_var) {
+            config.destination = value
+                .parse()
+                .map_err(|e: ParseLogOutputError| invalid(file_var, e.to_string()))?;
+            switch = switch.or(Some(true));
+        }
+
+        config.enabled = switch.unwrap_or(config.enabled);
+        Ok(config)
+    }
+
+    /// Installs this configuration as the process-wide logger used by the `debug!`, `info!`,
+    /// `warn!` and `error!` macros, replacing any logger installed before.
+    ///
+    /// Each record is written as one line with a UTC timestamp, the level, the module it
+    /// came from and any fields, laid out according to `format`. With `LogFormat::Text`
+    /// that looks like `2024-03-01T09:05:00.042Z INFO  [myapp::sync] done peers=3`.
+    /// A `LogOutput::File` destination is rotated according to `rotation`; writes and
+    /// rotation happen under one lock, so records from several threads never interleave
+    /// with a rotation. With `sinks`, each record is written to every sink whose level
+    /// it reaches, in that sink's format.
+    /// # Errors:
+    /// Returns an error if a `LogOutput::File` destination cannot be opened for appending,
+    /// or if `rotation.compress` is set without the `gzip` cargo feature.
+    pub fn init(&self) -> std::io::Result<()> {
+        logger::install(self)
+    }
+
+    /// Installs this configuration like `init`, but hands records to a background thread
+    /// instead of writing them on the calling thread.
+    ///
+    /// Records are queued on a bounded channel; the thread formats and writes them in
+    /// batches of up to `options.batch_size`. When the queue is full,
+    /// `options.overflow` decides whether the caller waits or a record is discarded.
+    /// Keep the returned guard alive for as long as records should be written in the
+    /// background: dropping it writes the records still queued, so none are lost at exit.
+    /// # Examples:
+    /// ```
+    /// use cli_utils::config::{Logging, LogOutput, NonBlocking, Overflow};
+    /// use cli_utils::info;
+    /// let config = Logging{ enabled: true, destination: LogOutput::Stderr, ..Logging::new() };
+    /// let guard = config.init_nonblocking(NonBlocking{ overflow: Overflow::DropOldest, ..NonBlocking::new() }).unwrap();
+    /// info!("written by the background thread");
+    /// drop(guard);
+    /// ```
+    /// # Errors:
+    /// Returns the errors of `init`, or an error if the thread cannot be spawned.
+    pub fn init_nonblocking(&self, options: NonBlocking) -> std::io::Result<LogGuard> {
+        logger::install_nonblocking(self, options)
+    }
+}
+
+impl Default for Logging {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vars<'a>(pairs: &'a [(&'a str, &'a str)]) -> impl Fn(&str) -> Option<String> + 'a {
+        move |name| {
+            pairs
+                .iter()
+                .find(|(key, _)| *key == name)
+                .map(|(_, value)| value.to_string())
+        }
+    }
+
+    #[test]
+    fn test_from_vars_defaults_when_unset() {
+        assert_eq!(
+            Logging::from_vars("app", vars(&[])).unwrap(),
+            Logging::new()
+        );
+    }
+
+    #[test]
+    fn test_from_vars_reads_all_three_variables() {
+        let env = [
+            ("MY_APP_LOG", "on"),
+            ("MY_APP_LOG_LEVEL", "Error"),
+            ("MY_APP_LOG_FILE", "/tmp/app.log"),
+        ];
+        let config = Logging::from_vars("my-app", vars(&env)).unwrap();
+        assert_eq!(
+            config,
+            Logging {
+                enabled: true,
+                level: LogLevel::Error,
+                destination: LogOutput::File("/tmp/app.log".to_string()),
+                directives: Directives::new(),
+                format: LogFormat::Text,
+                rotation: Rotation::new(),
+                sinks: Vec::new(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_from_vars_accepts_directives() {
+        let env = [("APP_LOG", "warn,app::sync=debug")];
+        let config = Logging::from_vars("app", vars(&env)).unwrap();
+        assert!(config.enabled);
+        assert!(config.allows(LogLevel::Debug, "app::sync"));
+        assert!(!config.allows(LogLevel::Info, "app"));
+        assert_eq!(config.max_level(), Some(LogLevel::Debug));
+        assert!(Logging::from_vars("app", vars(&[("APP_LOG", "a=b")])).is_err());
+    }
+
+    #[test]
+    fn test_max_level_ignores_level_when_directives_set_a_default() {
+        let config = Logging {
+            enabled: true,
+            level: LogLevel::Debug,
+            ..Logging::new()
+        };
+        assert_eq!(config.max_level(), Some(LogLevel::Debug));
+        let config = config.with_directives("off,db=error").unwrap();
+        assert_eq!(config.max_level(), Some(LogLevel::Error));
+        let config = config.with_directives("off").unwrap();
+        assert_eq!(config.max_level(), None);
+    }
+
+    #[test]
+    fn test_sinks_raise_the_most_verbose_level() {
+        let config = Logging {
+            enabled: true,
+            level: LogLevel::Debug,
+            ..Logging::new()
+        }
+        .with_sink(Sink {
+            level: LogLevel::Warn,
+            ..Sink::new(LogOutput::Stderr)
+        })
+        .with_sink(Sink {
+            level: LogLevel::Info,
+            ..Sink::new(LogOutput::Stdout)
+        });
+        assert!(!config.allows(LogLevel::Debug, "app"));
+        assert!(config.allows(LogLevel::Info, "app"));
+        assert_eq!(config.max_level(), Some(LogLevel::Info));
+        let config = config.with_directives("error").unwrap();
+        assert_eq!(config.max_level(), Some(LogLevel::Error));
+    }
+
+    #[test]
+    fn test_from_vars_log_off_wins() {
+        let env = [("APP_LOG", "off"), ("APP_LOG_LEVEL", "debug")];
+        let config = Logging::from_vars("app", vars(&env)).unwrap();
+        assert!(!config.enabled);
+        assert_eq!(config.level, LogLevel::Debug);
+    }
+
+    #[test]
+    fn test_from_vars_names_the_bad_variable() {
+        let error = Logging::from_vars("app", vars(&[("APP_LOG_LEVEL", "loud")])).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "APP_LOG_LEVEL: invalid log level \"loud\": expected one of debug, info, warn, error"
+        );
+        let error = Logging::from_vars("app", vars(&[("APP_LOG", "maybe")])).unwrap_err();
+        assert_eq!(error.var, "APP_LOG");
+        let error = Logging::from_vars("app", vars(&[("APP_LOG_FILE", " ")])).unwrap_err();
+        assert_eq!(error.var, "APP_LOG_FILE");
     }
-}
\ No newline at end of file
+}
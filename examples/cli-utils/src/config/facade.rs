@@ -0,0 +1,130 @@
+//! Routing records from the `log` crate through the configured logger.
+//!
+//! Only compiled with the `log` cargo feature.
+
+use super::{logger, LogLevel, Logging};
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Set once `init_log_facade` has registered the facade. From then on, installing a
+/// configuration also updates the `log` crate's maximum level.
+static REGISTERED: AtomicBool = AtomicBool::new(false);
+
+/// Keeps `log::max_level` in step with `config`, the configuration being installed.
+pub(super) fn sync_max_level(config: &Logging) {
+    if REGISTERED.load(Ordering::Acquire) {
+        log::set_max_level(config.level_filter());
+    }
+}
+
+impl From<LogLevel> for log::Level {
+    fn from(level: LogLevel) -> Self {
+        match level {
+            LogLevel::Debug => log::Level::Debug,
+            LogLevel::Info => log::Level::Info,
+            LogLevel::Warn => log::Level::Warn,
+            LogLevel::Error => log::Level::Error,
+        }
+    }
+}
+
+/// `log::Level::Trace` has no counterpart and becomes `LogLevel::Debug`.
+impl From<log::Level> for LogLevel {
+    fn from(level: log::Level) -> Self {
+        match level {
+            log::Level::Trace | log::Level::Debug => LogLevel::Debug,
+            log::Level::Info => LogLevel::Info,
+            log::Level::Warn => LogLevel::Warn,
+            log::Level::Error => LogLevel::Error,
+        }
+    }
+}
+
+impl From<LogLevel> for log::LevelFilter {
+    fn from(level: LogLevel) -> Self {
+        log::Level::from(level).to_level_filter()
+    }
+}
+
+/// Fails only for `log::LevelFilter::Off`, which has no `LogLevel`.
+impl TryFrom<log::LevelFilter> for LogLevel {
+    type Error = ();
+
+    fn try_from(filter: log::LevelFilter) -> Result<Self, ()> {
+        filter.to_level().map(LogLevel::from).ok_or(())
+    }
+}
+
+impl Logging {
+    /// Returns the most verbose `log` level this configuration lets through for any target.
+    pub fn level_filter(&self) -> log::LevelFilter {
+        self.max_level()
+            .map_or(log::LevelFilter::Off, log::LevelFilter::from)
+    }
+
+    /// Installs this configuration with `init` and then registers the `log` crate's global
+    /// logger, so records from dependencies go to the same destination.
+    ///
+    /// The `log` crate only accepts one global logger per process, so this can succeed once.
+    /// Later calls fail without changing the installed destination or level. Configurations
+    /// installed afterwards with `init` apply to `log` records too, including their level.
+    /// # Examples:
+    /// ```
+    /// use cli_utils::config::{Logging, LogLevel, LogOutput};
+    /// let config = Logging{ enabled: true, level: LogLevel::Info, destination: LogOutput::Stderr, ..Logging::new() };
+    /// config.init_log_facade().unwrap();
+    /// log::info!(target: "hyper", "connection established");
+    /// ```
+    pub fn init_log_facade(&self) -> Result<(), InitError> {
+        let logger = logger::open(self).map_err(InitError::Io)?;
+        log::set_logger(&Facade).map_err(InitError::SetLogger)?;
+        REGISTERED.store(true, Ordering::Release);
+        logger::replace(logger);
+        Ok(())
+    }
+}
+
+/// The `log` crate's global logger. Records are filtered and written by whichever
+/// configuration is installed at the time, see `Logging::allows`.
+struct Facade;
+
+impl log::Log for Facade {
+    fn enabled(&self, metadata: &log::Metadata<'_>) -> bool {
+        logger::log_enabled(metadata.level().into(), metadata.target())
+    }
+
+    fn log(&self, record: &log::Record<'_>) {
+        logger::log(record.level().into(), record.target(), *record.args());
+    }
+
+    fn flush(&self) {
+        logger::flush();
+    }
+}
+
+/// The error returned by `Logging::init_log_facade`.
+#[derive(Debug)]
+pub enum InitError {
+    /// The destination could not be opened.
+    Io(std::io::Error),
+    /// Another logger was already registered with the `log` crate.
+    SetLogger(log::SetLoggerError),
+}
+
+impl fmt::Display for InitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InitError::Io(error) => write!(f, "could not open log destination: {}", error),
+            InitError::SetLogger(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl std::error::Error for InitError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            InitError::Io(error) => Some(error),
+            InitError::SetLogger(error) => Some(error),
+        }
+    }
+}
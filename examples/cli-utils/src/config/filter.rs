@@ -0,0 +1,270 @@
+//! Per-target level directives in the style of `RUST_LOG`, such as `info,myapp::sync=debug,hyper=off`.
+
+use super::LogLevel;
+use std::fmt;
+use std::str::FromStr;
+
+/// One `target=level` rule. A missing target applies to every record; a missing level means `off`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Directive {
+    pub target: Option<String>,
+    pub level: Option<LogLevel>,
+}
+
+impl Directive {
+    /// Returns true if the rule covers `target`: the same module or one nested inside it.
+    fn matches(&self, target: &str) -> bool {
+        match &self.target {
+            None => true,
+            Some(prefix) => match target.strip_prefix(prefix.as_str()) {
+                Some(rest) => rest.is_empty() || rest.starts_with("::"),
+                None => false,
+            },
+        }
+    }
+}
+
+impl fmt::Display for Directive {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let level = match self.level {
+            Some(level) => level.to_string(),
+            None => "off".to_string(),
+        };
+        match &self.target {
+            Some(target) => write!(f, "{}={}", target, level),
+            None => f.write_str(&level),
+        }
+    }
+}
+
+/// A list of directives, checked against the target of every record.
+///
+/// The directive with the longest matching target wins; a bare level applies to records
+/// no other directive matches. A bare target, such as `myapp::sync`, enables every level.
+/// # Examples:
+/// ```
+/// use cli_utils::config::{Directives, LogLevel};
+/// let directives: Directives = "warn,myapp::sync=debug,hyper=off".parse().unwrap();
+/// assert_eq!(directives.level_for("myapp::sync::peer"), Some(Some(LogLevel::Debug)));
+/// assert_eq!(directives.level_for("myapp::db"), Some(Some(LogLevel::Warn)));
+/// assert_eq!(directives.level_for("hyper::client"), Some(None));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Directives {
+    rules: Vec<Directive>,
+}
+
+impl Directives {
+    /// Creates an empty list, which leaves every record to `Logging::level`.
+    pub fn new() -> Self {
+        Self { rules: Vec::new() }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Adds a rule; later rules for the same target replace earlier ones.
+    pub fn push(&mut self, directive: Directive) {
+        self.rules.retain(|rule| rule.target != directive.target);
+        self.rules.push(directive);
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Directive> {
+        self.rules.iter()
+    }
+
+    /// Returns the level of the directive that applies to `target`, or `None` if no directive does.
+    ///
+    /// `Some(None)` means the target is turned off.
+    pub fn level_for(&self, target: &str) -> Option<Option<LogLevel>> {
+        self.rules
+            .iter()
+            .filter(|rule| rule.matches(target))
+            .max_by_key(|rule| rule.target.as_ref().map_or(0, |t| t.len() + 1))
+            .map(|rule| rule.level)
+    }
+}
+
+impl FromStr for Directives {
+    type Err = ParseDirectiveError;
+
+    /// Parses comma-separated directives: `level`, `target=level` or a bare `target`.
+    /// Levels are those accepted by `LogLevel`, plus `off` and `trace`, which is read as
+    /// `debug`. A bare word a typo away from a level, such as `warnn`, is an error rather
+    /// than a target.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut directives = Directives::new();
+        for part in s.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+            let error = || ParseDirectiveError(part.to_string());
+            let parse_level = |level: &str| -> Result<Option<LogLevel>, ParseDirectiveError> {
+                let level = level.trim();
+                if level.eq_ignore_ascii_case("off") {
+                    Ok(None)
+                } else if level.eq_ignore_ascii_case("trace") {
+                    // `LogLevel` has nothing finer than debug.
+                    Ok(Some(LogLevel::Debug))
+                } else {
+                    level.parse().map(Some).map_err(|_| error())
+                }
+            };
+            let directive = match part.split_once('=') {
+                Some((target, level)) => {
+                    let target = target.trim();
+                    if target.is_empty() {
+                        return Err(error());
+                    }
+                    Directive {
+                        target: Some(target.to_string()),
+                        level: parse_level(level)?,
+                    }
+                }
+                None => match parse_level(part) {
+                    Ok(level) => Directive {
+                        target: None,
+                        level,
+                    },
+                    Err(_) if is_target(part) => Directive {
+                        target: Some(part.to_string()),
+                        level: Some(LogLevel::Debug),
+                    },
+                    Err(e) => return Err(e),
+                },
+            };
+            directives.push(directive);
+        }
+        Ok(directives)
+    }
+}
+
+/// Returns true if `s` can be a bare target: a module path, or a crate name that is not
+/// a misspelt level.
+fn is_target(s: &str) -> bool {
+    let valid = s.split("::").all(|part| {
+        !part.is_empty()
+            && part
+                .chars()
+                .all(|c| c.is_alphanumeric() || c == '_' || c == '-')
+    });
+    valid && (s.contains("::") || !is_level_typo(s))
+}
+
+/// Returns true if `word` is one edit (a character added, removed, replaced or two
+/// neighbours swapped) away from a level name.
+fn is_level_typo(word: &str) -> bool {
+    let word = word.to_ascii_lowercase();
+    ["trace", "debug", "info", "warn", "warning", "error", "off"]
+        .iter()
+        .any(|level| edit_distance(&word, level) <= 1)
+}
+
+/// The optimal string alignment distance between `a` and `b`.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let (a, b): (Vec<char>, Vec<char>) = (a.chars().collect(), b.chars().collect());
+    let mut rows = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in rows.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    rows[0] = (0..=b.len()).collect();
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut best = (rows[i - 1][j] + 1)
+                .min(rows[i][j - 1] + 1)
+                .min(rows[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                best = best.min(rows[i - 2][j - 2] + 1);
+            }
+            rows[i][j] = best;
+        }
+    }
+    rows[a.len()][b.len()]
+}
+
+impl fmt::Display for Directives {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, rule) in self.rules.iter().enumerate() {
+            if i > 0 {
+                f.write_str(",")?;
+            }
+            write!(f, "{}", rule)?;
+        }
+        Ok(())
+    }
+}
+
+/// The error returned for a directive that is not `level`, `target=level` or `target`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseDirectiveError(String);
+
+impl fmt::Display for ParseDirectiveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid log directive {:?}: expected `level`, `target=level` or `target`, \
+             with level one of off, debug, info, warn, error",
+            self.0
+        )
+    }
+}
+
+impl std::error::Error for ParseDirectiveError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_longest_prefix_wins_on_module_boundaries() {
+        let directives: Directives = "myapp=warn,myapp::sync=debug".parse().unwrap();
+        assert_eq!(directives.level_for("myapp"), Some(Some(LogLevel::Warn)));
+        assert_eq!(
+            directives.level_for("myapp::sync"),
+            Some(Some(LogLevel::Debug))
+        );
+        assert_eq!(
+            directives.level_for("myapp::synchronize"),
+            Some(Some(LogLevel::Warn))
+        );
+        assert_eq!(directives.level_for("myappx"), None);
+    }
+
+    #[test]
+    fn test_later_directives_replace_earlier_ones() {
+        let directives: Directives = "info, hyper=debug ,hyper=off,error".parse().unwrap();
+        assert_eq!(directives.to_string(), "hyper=off,error");
+    }
+
+    #[test]
+    fn test_trace_is_read_as_debug() {
+        let directives: Directives = "trace,hyper=TRACE".parse().unwrap();
+        assert_eq!(directives.to_string(), "debug,hyper=debug");
+    }
+
+    #[test]
+    fn test_bare_target_enables_everything() {
+        let directives: Directives = "off,myapp::sync".parse().unwrap();
+        assert_eq!(
+            directives.level_for("myapp::sync"),
+            Some(Some(LogLevel::Debug))
+        );
+        assert_eq!(directives.level_for("other"), Some(None));
+    }
+
+    #[test]
+    fn test_invalid_directives() {
+        assert!("myapp=loud".parse::<Directives>().is_err());
+        assert!("=info".parse::<Directives>().is_err());
+        assert!("my app".parse::<Directives>().is_err());
+        for typo in ["warnn", "eror", "Infoo", "debg", "wran", "of"] {
+            assert!(typo.parse::<Directives>().is_err(), "{}", typo);
+        }
+        let directives: Directives = "log,hyper,warnn::io".parse().unwrap();
+        assert_eq!(directives.level_for("hyper"), Some(Some(LogLevel::Debug)));
+        assert_eq!(
+            directives.level_for("warnn::io"),
+            Some(Some(LogLevel::Debug))
+        );
+        assert!("".parse::<Directives>().unwrap().is_empty());
+    }
+}
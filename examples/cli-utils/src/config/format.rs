@@ -0,0 +1,396 @@
+//! Rendering log records as human-readable text, JSON lines or logfmt.
+
+use super::time::DateTime;
+use super::LogLevel;
+use crate::colors::{color_depth, Color, Style};
+use std::fmt::{self, Write};
+use std::str::FromStr;
+
+/// How each log record is laid out on its line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LogFormat {
+    /// `2024-03-01T09:05:00.042Z INFO  [myapp] message key=value`
+    #[default]
+    Text,
+    /// `{"timestamp":"2024-03-01T09:05:00.042Z","level":"info","target":"myapp","message":"message","fields":{"key":"value"}}`
+    Json,
+    /// `ts=2024-03-01T09:05:00.042Z level=info target=myapp msg=message key=value`
+    Logfmt,
+}
+
+impl fmt::Display for LogFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(match self {
+            LogFormat::Text => "text",
+            LogFormat::Json => "json",
+            LogFormat::Logfmt => "logfmt",
+        })
+    }
+}
+
+impl FromStr for LogFormat {
+    type Err = ParseLogFormatError;
+
+    /// Parses `text`, `json` or `logfmt`, case-insensitively.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "text" => Ok(LogFormat::Text),
+            "json" => Ok(LogFormat::Json),
+            "logfmt" => Ok(LogFormat::Logfmt),
+            _ => Err(ParseLogFormatError(s.to_string())),
+        }
+    }
+}
+
+/// The error returned when a string is not a log format.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseLogFormatError(String);
+
+impl fmt::Display for ParseLogFormatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid log format {:?}: expected one of text, json, logfmt",
+            self.0
+        )
+    }
+}
+
+impl std::error::Error for ParseLogFormatError {}
+
+/// The value of a key-value field attached to a log record.
+///
+/// Numbers and booleans are written unquoted in JSON; anything else can be attached
+/// as a string, e.g. with `.to_string()`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FieldValue {
+    Str(String),
+    Int(i64),
+    Uint(u64),
+    Float(f64),
+    Bool(bool),
+}
+
+macro_rules! field_value_from {
+    ($variant:ident: $($ty:ty),+) => {
+        $(
+            impl From<$ty> for FieldValue {
+                fn from(value: $ty) -> Self {
+                    FieldValue::$variant(value.into())
+                }
+            }
+        )+
+    };
+}
+
+field_value_from!(Int: i8, i16, i32, i64);
+field_value_from!(Uint: u8, u16, u32, u64);
+field_value_from!(Float: f32, f64);
+field_value_from!(Bool: bool);
+field_value_from!(Str: String, &str, &String, char);
+
+impl From<usize> for FieldValue {
+    fn from(value: usize) -> Self {
+        FieldValue::Uint(value as u64)
+    }
+}
+
+impl From<isize> for FieldValue {
+    fn from(value: isize) -> Self {
+        FieldValue::Int(value as i64)
+    }
+}
+
+impl fmt::Display for FieldValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FieldValue::Str(s) => f.write_str(s),
+            FieldValue::Int(n) => write!(f, "{}", n),
+            FieldValue::Uint(n) => write!(f, "{}", n),
+            FieldValue::Float(n) => write!(f, "{}", n),
+            FieldValue::Bool(b) => write!(f, "{}", b),
+        }
+    }
+}
+
+/// A key-value pair attached to a record at the call site.
+pub type Field<'a> = (&'a str, FieldValue);
+
+/// The style of each level's label, the same as its role in `Theme::dark`.
+const fn level_style(level: LogLevel) -> Style {
+    match level {
+        LogLevel::Debug => Style::new().fg(Color::BrightBlack),
+        LogLevel::Info => Style::new().fg(Color::BrightCyan),
+        LogLevel::Warn => Style::new().fg(Color::BrightYellow),
+        LogLevel::Error => Style::new().bold().fg(Color::BrightRed),
+    }
+}
+
+/// Formats one record, including the trailing newline.
+///
+/// With `colored`, the level of a `LogFormat::Text` record is painted like the matching
+/// role of the dark theme; the other formats are meant for machines and never colored.
+pub(crate) fn format_record<K: AsRef<str>>(
+    format: LogFormat,
+    time: &DateTime,
+    level: LogLevel,
+    target: &str,
+    message: &str,
+    fields: &[(K, FieldValue)],
+    colored: bool,
+) -> String {
+    let mut line = String::with_capacity(64 + message.len());
+    match format {
+        LogFormat::Text => {
+            let label = level.label();
+            let padding = " ".repeat(5 - label.len());
+            let label = if colored {
+                level_style(level).paint_for(label, color_depth())
+            } else {
+                label.to_string()
+            };
+            let _ = write!(
+                line,
+                "{} {}{} [{}] {}",
+                time.rfc3339(),
+                label,
+                padding,
+                target,
+                message
+            );
+            for (key, value) in fields {
+                line.push(' ');
+                write_logfmt_pair(&mut line, key.as_ref(), &value.to_string());
+            }
+        }
+        LogFormat::Json => {
+            line.push_str("{\"timestamp\":");
+            write_json_string(&mut line, &time.rfc3339());
+            line.push_str(",\"level\":");
+            write_json_string(&mut line, &level.to_string());
+            line.push_str(",\"target\":");
+            write_json_string(&mut line, target);
+            line.push_str(",\"message\":");
+            write_json_string(&mut line, message);
+            if !fields.is_empty() {
+                line.push_str(",\"fields\":{");
+                for (i, (key, value)) in fields.iter().enumerate() {
+                    if i > 0 {
+                        line.push(',');
+                    }
+                    write_json_string(&mut line, key.as_ref());
+                    line.push(':');
+                    write_json_value(&mut line, value);
+                }
+                line.push('}');
+            }
+            line.push('}');
+        }
+        LogFormat::Logfmt => {
+            write_logfmt_pair(&mut line, "ts", &time.rfc3339());
+            line.push(' ');
+            write_logfmt_pair(&mut line, "level", &level.to_string());
+            line.push(' ');
+            write_logfmt_pair(&mut line, "target", target);
+            line.push(' ');
+            write_logfmt_pair(&mut line, "msg", message);
+            for (key, value) in fields {
+                line.push(' ');
+                write_logfmt_pair(&mut line, key.as_ref(), &value.to_string());
+            }
+        }
+    }
+    line.push('\n');
+    line
+}
+
+fn write_json_value(out: &mut String, value: &FieldValue) {
+    match value {
+        FieldValue::Str(s) => write_json_string(out, s),
+        // JSON has no NaN or infinity.
+        FieldValue::Float(n) if !n.is_finite() => out.push_str("null"),
+        other => {
+            let _ = write!(out, "{}", other);
+        }
+    }
+}
+
+/// Writes `s` as a JSON string literal, escaping quotes, backslashes and control characters.
+fn write_json_string(out: &mut String, s: &str) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            '\u{8}' => out.push_str("\\b"),
+            '\u{c}' => out.push_str("\\f"),
+            c if c.is_control() => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+/// Writes `key=value`, quoting the value if it is empty or contains spaces, `=`, quotes
+/// or control characters. Characters that are not allowed in a key are replaced with `_`.
+pub(super) fn write_logfmt_pair(out: &mut String, key: &str, value: &str) {
+    for c in key.chars() {
+        if c > ' ' && c != '=' && c != '"' && !c.is_control() {
+            out.push(c);
+        } else {
+            out.push('_');
+        }
+    }
+    out.push('=');
+    let needs_quotes = value.is_empty()
+        || value
+            .chars()
+            .any(|c| c == ' ' || c == '=' || c == '"' || c == '\\' || c.is_control());
+    if !needs_quotes {
+        out.push_str(value);
+        return;
+    }
+    out.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c.is_control() => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::colors::Role;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    fn time() -> DateTime {
+        DateTime::from_system_time(UNIX_EPOCH + Duration::from_millis(1_709_283_900_042))
+    }
+
+    #[test]
+    fn test_json_escapes_quotes_newlines_and_control_characters() {
+        let fields = [
+            ("path", FieldValue::from("C:\\tmp\\\"x\"")),
+            ("count", FieldValue::from(3u8)),
+            ("ratio", FieldValue::from(f64::NAN)),
+            ("ok", FieldValue::from(false)),
+        ];
+        let line = format_record(
+            LogFormat::Json,
+            &time(),
+            LogLevel::Warn,
+            "app",
+            "line one\nline\ttwo\u{1}",
+            &fields,
+            false,
+        );
+        assert_eq!(
+            line,
+            "{\"timestamp\":\"2024-03-01T09:05:00.042Z\",\"level\":\"warn\",\"target\":\"app\",\
+             \"message\":\"line one\\nline\\ttwo\\u0001\",\
+             \"fields\":{\"path\":\"C:\\\\tmp\\\\\\\"x\\\"\",\"count\":3,\"ratio\":null,\"ok\":false}}\n"
+        );
+    }
+
+    #[test]
+    fn test_logfmt_quotes_only_when_needed() {
+        let fields = [
+            ("user", FieldValue::from("bob")),
+            ("query", FieldValue::from("a = \"b\"")),
+            ("empty", FieldValue::from("")),
+            ("bad key", FieldValue::from(-1)),
+        ];
+        let line = format_record(
+            LogFormat::Logfmt,
+            &time(),
+            LogLevel::Info,
+            "app::db",
+            "slow query",
+            &fields,
+            false,
+        );
+        assert_eq!(
+            line,
+            "ts=2024-03-01T09:05:00.042Z level=info target=app::db msg=\"slow query\" \
+             user=bob query=\"a = \\\"b\\\"\" empty=\"\" bad_key=-1\n"
+        );
+    }
+
+    #[test]
+    fn test_text_appends_fields() {
+        let fields = [("attempt", FieldValue::from(2usize))];
+        let line = format_record(
+            LogFormat::Text,
+            &time(),
+            LogLevel::Error,
+            "app",
+            "retrying",
+            &fields,
+            false,
+        );
+        assert_eq!(
+            line,
+            "2024-03-01T09:05:00.042Z ERROR [app] retrying attempt=2\n"
+        );
+    }
+
+    #[test]
+    fn test_colored_text_only_paints_the_level() {
+        let fields = [("attempt", FieldValue::from(2usize))];
+        let plain = format_record(
+            LogFormat::Text,
+            &time(),
+            LogLevel::Warn,
+            "app",
+            "retrying",
+            &fields,
+            false,
+        );
+        let colored = format_record(
+            LogFormat::Text,
+            &time(),
+            LogLevel::Warn,
+            "app",
+            "retrying",
+            &fields,
+            true,
+        );
+        assert_ne!(colored, plain);
+        assert_eq!(crate::colors::strip_ansi(&colored), plain);
+        assert!(plain.contains(" WARN  [app] "));
+        let json = format_record(
+            LogFormat::Json,
+            &time(),
+            LogLevel::Warn,
+            "app",
+            "retrying",
+            &fields,
+            true,
+        );
+        assert!(!json.contains('\x1b'));
+        let dark = crate::colors::Theme::dark();
+        for (level, role) in [
+            (LogLevel::Debug, Role::Muted),
+            (LogLevel::Info, Role::Info),
+            (LogLevel::Warn, Role::Warning),
+            (LogLevel::Error, Role::Error),
+        ] {
+            assert_eq!(level_style(level), dark.style(role));
+        }
+    }
+}
@@ -0,0 +1,96 @@
+//! Encoding records for the native protocol of the systemd journal.
+
+#[cfg(unix)]
+use super::format::FieldValue;
+#[cfg(unix)]
+use super::LogLevel;
+
+/// The socket `LogOutput::Journald` connects to unless given another path.
+pub(crate) const DEFAULT_SOCKET: &str = "/run/systemd/journal/socket";
+
+/// Encodes one record as a journal entry: `MESSAGE`, `PRIORITY`, `SYSLOG_IDENTIFIER` and
+/// `TARGET`, followed by the fields with their keys upper-cased into journal field names.
+///
+/// Values containing a newline use the protocol's length-prefixed binary form.
+#[cfg(unix)]
+pub(crate) fn encode<K: AsRef<str>>(
+    level: LogLevel,
+    app_name: &str,
+    target: &str,
+    message: &str,
+    fields: &[(K, FieldValue)],
+) -> Vec<u8> {
+    let mut out = Vec::with_capacity(128 + message.len());
+    write_field(&mut out, "MESSAGE", message);
+    write_field(&mut out, "PRIORITY", &level.priority().to_string());
+    write_field(&mut out, "SYSLOG_IDENTIFIER", app_name);
+    write_field(&mut out, "TARGET", target);
+    for (key, value) in fields {
+        write_field(&mut out, &field_name(key.as_ref()), &value.to_string());
+    }
+    out
+}
+
+#[cfg(unix)]
+fn write_field(out: &mut Vec<u8>, name: &str, value: &str) {
+    out.extend_from_slice(name.as_bytes());
+    if value.contains('\n') {
+        out.push(b'\n');
+        out.extend_from_slice(&(value.len() as u64).to_le_bytes());
+    } else {
+        out.push(b'=');
+    }
+    out.extend_from_slice(value.as_bytes());
+    out.push(b'\n');
+}
+
+/// Turns a field key into a valid journal field name: upper-case ASCII letters, digits
+/// and underscores, starting with a letter and at most 64 bytes long.
+#[cfg(unix)]
+fn field_name(key: &str) -> String {
+    let name: String = key
+        .chars()
+        .map(|c| match c {
+            'a'..='z' => c.to_ascii_uppercase(),
+            'A'..='Z' | '0'..='9' => c,
+            _ => '_',
+        })
+        .collect();
+    // Names starting with an underscore are reserved for fields the journal adds itself.
+    let name = name.trim_start_matches('_');
+    let mut name = if name.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        name.to_string()
+    } else {
+        format!("FIELD_{}", name)
+    };
+    name.truncate(64);
+    name
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_writes_fields_and_binary_values() {
+        let fields = [
+            ("peer-id", FieldValue::from(7)),
+            ("_private", FieldValue::from("x")),
+            ("2fa", FieldValue::from(true)),
+        ];
+        let entry = encode(
+            LogLevel::Error,
+            "myapp",
+            "myapp::sync",
+            "two\nlines",
+            &fields,
+        );
+        let mut expected = b"MESSAGE\n".to_vec();
+        expected.extend_from_slice(&9u64.to_le_bytes());
+        expected.extend_from_slice(
+            b"two\nlines\nPRIORITY=3\nSYSLOG_IDENTIFIER=myapp\nTARGET=myapp::sync\n\
+              PEER_ID=7\nPRIVATE=x\nFIELD_2FA=true\n",
+        );
+        assert_eq!(entry, expected);
+    }
+}
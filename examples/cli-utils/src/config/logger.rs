@@ -0,0 +1,364 @@
+//! The process-wide logger installed by `Logging::init` and the functions behind the logging macros.
+
+use super::format::{format_record, Field, FieldValue};
+use super::nonblocking::{run_worker, LogGuard, NonBlocking, Queue};
+use super::rotate::RotatingFile;
+use super::time::DateTime;
+#[cfg(unix)]
+use super::{journald, syslog};
+use super::{LogFormat, LogLevel, LogOutput, Logging, Rotation, Sink};
+use crate::colors::{colors_enabled, ColorChoice, Stream};
+use std::fmt;
+use std::io::{self, Write};
+#[cfg(unix)]
+use std::os::unix::net::UnixDatagram;
+use std::path::Path;
+use std::sync::{Arc, Mutex, RwLock};
+use std::thread;
+
+enum Writer {
+    Stdout,
+    Stderr,
+    File(RotatingFile),
+    #[cfg(unix)]
+    Syslog(UnixDatagram),
+    #[cfg(unix)]
+    Journald(UnixDatagram),
+}
+
+impl Writer {
+    fn open(output: &LogOutput, rotation: Rotation) -> io::Result<Writer> {
+        Ok(match output {
+            LogOutput::Stdout => Writer::Stdout,
+            LogOutput::Stderr => Writer::Stderr,
+            LogOutput::File(path) => Writer::File(RotatingFile::open(Path::new(path), rotation)?),
+            #[cfg(unix)]
+            LogOutput::Syslog(path) => Writer::Syslog(connect(path)?),
+            #[cfg(unix)]
+            LogOutput::Journald(path) => Writer::Journald(connect(path)?),
+            #[cfg(not(unix))]
+            LogOutput::Syslog(_) | LogOutput::Journald(_) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::Unsupported,
+                    format!("{} output is only available on Unix", output),
+                ))
+            }
+        })
+    }
+
+    fn write_line(&mut self, line: &str) -> io::Result<()> {
+        match self {
+            Writer::Stdout => io::stdout().lock().write_all(line.as_bytes()),
+            Writer::Stderr => io::stderr().lock().write_all(line.as_bytes()),
+            Writer::File(file) => file.write_line(line),
+            #[cfg(unix)]
+            Writer::Syslog(socket) | Writer::Journald(socket) => {
+                socket.send(line.as_bytes()).map(drop)
+            }
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Writer::Stdout => io::stdout().flush(),
+            Writer::Stderr => io::stderr().flush(),
+            Writer::File(file) => file.flush(),
+            #[cfg(unix)]
+            Writer::Syslog(_) | Writer::Journald(_) => Ok(()),
+        }
+    }
+}
+
+#[cfg(unix)]
+fn connect(path: &str) -> io::Result<UnixDatagram> {
+    let socket = UnixDatagram::unbound()?;
+    socket.connect(path)?;
+    Ok(socket)
+}
+
+/// An opened `Sink`.
+struct Output {
+    level: LogLevel,
+    format: LogFormat,
+    colored: bool,
+    writer: Mutex<Writer>,
+}
+
+impl Output {
+    fn open(sink: &Sink) -> io::Result<Output> {
+        let colored = match (sink.color, &sink.output) {
+            (ColorChoice::Never, _) => false,
+            (ColorChoice::Always, _) => true,
+            (ColorChoice::Auto, LogOutput::Stdout) => colors_enabled(Stream::Stdout),
+            (ColorChoice::Auto, LogOutput::Stderr) => colors_enabled(Stream::Stderr),
+            (ColorChoice::Auto, _) => false,
+        };
+        Ok(Output {
+            level: sink.level,
+            format: sink.format,
+            colored,
+            writer: Mutex::new(Writer::open(&sink.output, sink.rotation)?),
+        })
+    }
+
+    /// Writes one record with `writer`, which must be this output's locked writer.
+    ///
+    /// The syslog and journal sockets carry the level and the fields themselves, so
+    /// `format` only applies to the other outputs.
+    fn write<K: AsRef<str>>(
+        &self,
+        writer: &mut Writer,
+        time: &DateTime,
+        level: LogLevel,
+        target: &str,
+        message: &str,
+        fields: &[(K, FieldValue)],
+    ) -> io::Result<()> {
+        match writer {
+            #[cfg(unix)]
+            Writer::Syslog(socket) => {
+                let message =
+                    syslog::encode(time, level, syslog::app_name(), target, message, fields);
+                socket.send(message.as_bytes()).map(drop)
+            }
+            #[cfg(unix)]
+            Writer::Journald(socket) => {
+                let entry = journald::encode(level, syslog::app_name(), target, message, fields);
+                socket.send(&entry).map(drop)
+            }
+            _ => {
+                let line = format_record(
+                    self.format,
+                    time,
+                    level,
+                    target,
+                    message,
+                    fields,
+                    self.colored,
+                );
+                writer.write_line(&line)
+            }
+        }
+    }
+
+    fn flush(&self) {
+        let _ = self.writer.lock().map(|mut w| w.flush());
+    }
+}
+
+/// A record handed to the writer thread of a non-blocking logger.
+pub(super) struct Record {
+    time: DateTime,
+    level: LogLevel,
+    target: String,
+    message: String,
+    fields: Vec<(String, FieldValue)>,
+}
+
+pub(super) struct Logger {
+    config: Logging,
+    outputs: Arc<Vec<Output>>,
+    /// The queue of the writer thread, when installed by `Logging::init_nonblocking`.
+    queue: Option<Arc<Queue<Record>>>,
+}
+
+static LOGGER: RwLock<Option<Logger>> = RwLock::new(None);
+
+fn open_outputs(config: &Logging) -> io::Result<Arc<Vec<Output>>> {
+    let outputs = config
+        .effective_sinks()
+        .iter()
+        .map(Output::open)
+        .collect::<io::Result<Vec<_>>>()?;
+    Ok(Arc::new(outputs))
+}
+
+/// Installs `config` as the process-wide logger, replacing any previous one.
+pub(super) fn install(config: &Logging) -> io::Result<()> {
+    replace(open(config)?);
+    Ok(())
+}
+
+/// Opens the outputs of `config` without installing anything, so callers can check that
+/// the rest of their setup succeeds before `replace` swaps the logger in.
+pub(super) fn open(config: &Logging) -> io::Result<Logger> {
+    Ok(Logger {
+        config: config.clone(),
+        outputs: open_outputs(config)?,
+        queue: None,
+    })
+}
+
+/// Installs `config` as the process-wide logger with its writes moved to a background thread.
+pub(super) fn install_nonblocking(config: &Logging, options: NonBlocking) -> io::Result<LogGuard> {
+    let outputs = open_outputs(config)?;
+    let queue = Arc::new(Queue::new(options));
+    let worker = {
+        let outputs = Arc::clone(&outputs);
+        let queue = Arc::clone(&queue);
+        thread::Builder::new()
+            .name("log-writer".to_string())
+            .spawn(move || run_worker(&queue, |batch| write_batch(&outputs, batch)))?
+    };
+    replace(Logger {
+        config: config.clone(),
+        outputs,
+        queue: Some(Arc::clone(&queue)),
+    });
+    Ok(LogGuard::new(queue, worker))
+}
+
+pub(super) fn replace(logger: Logger) {
+    let mut global = LOGGER.write().unwrap_or_else(|e| e.into_inner());
+    if let Some(old) = global.as_ref() {
+        old.outputs.iter().for_each(Output::flush);
+    }
+    #[cfg(feature = "log")]
+    super::facade::sync_max_level(&logger.config);
+    *global = Some(logger);
+}
+
+/// Writes records taken off the queue, locking and flushing each output once per batch.
+fn write_batch(outputs: &[Output], batch: &[Record]) {
+    for output in outputs {
+        let mut writer = output.writer.lock().unwrap_or_else(|e| e.into_inner());
+        for record in batch.iter().filter(|r| r.level >= output.level) {
+            let _ = output.write(
+                &mut writer,
+                &record.time,
+                record.level,
+                &record.target,
+                &record.message,
+                &record.fields,
+            );
+        }
+        let _ = writer.flush();
+    }
+}
+
+/// Returns true if a record at `level` from `target` would be written by the installed logger.
+pub fn log_enabled(level: LogLevel, target: &str) -> bool {
+    let global = LOGGER.read().unwrap_or_else(|e| e.into_inner());
+    match global.as_ref() {
+        Some(logger) => logger.config.allows(level, target),
+        None => false,
+    }
+}
+
+/// Writes a record to the installed logger.
+///
+/// Records are dropped when no logger is installed or the configuration's filters,
+/// see `Logging::allows`, reject them.
+pub fn log(level: LogLevel, target: &str, args: fmt::Arguments<'_>) {
+    log_with(level, target, args, &[]);
+}
+
+/// Writes a record with key-value fields to the installed logger. This is what the
+/// logging macros expand to.
+pub fn log_with(level: LogLevel, target: &str, args: fmt::Arguments<'_>, fields: &[Field<'_>]) {
+    let global = LOGGER.read().unwrap_or_else(|e| e.into_inner());
+    let Some(logger) = global.as_ref() else {
+        return;
+    };
+    if !logger.config.allows(level, target) {
+        return;
+    }
+    let message = match args.as_str() {
+        Some(s) => s.to_string(),
+        None => args.to_string(),
+    };
+    let time = DateTime::now();
+    if let Some(queue) = &logger.queue {
+        let record = Record {
+            time,
+            level,
+            target: target.to_string(),
+            message,
+            fields: fields
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.clone()))
+                .collect(),
+        };
+        match queue.push(record) {
+            Ok(()) => {}
+            // The guard has been dropped and the writer thread is gone.
+            Err(record) => write_batch(&logger.outputs, &[record]),
+        }
+        return;
+    }
+    for output in logger.outputs.iter().filter(|o| level >= o.level) {
+        let mut writer = output.writer.lock().unwrap_or_else(|e| e.into_inner());
+        // A logger has nowhere to report its own write errors.
+        let _ = output.write(&mut writer, &time, level, target, &message, fields);
+    }
+}
+
+/// Flushes the installed logger's destinations, first waiting for the writer thread of
+/// a non-blocking logger to write every queued record.
+pub fn flush() {
+    let global = LOGGER.read().unwrap_or_else(|e| e.into_inner());
+    if let Some(logger) = global.as_ref() {
+        if let Some(queue) = &logger.queue {
+            queue.wait_until_idle();
+        }
+        logger.outputs.iter().for_each(Output::flush);
+    }
+}
+
+/// Logs a message at the given `LogLevel`, formatted like `format!`.
+///
+/// The record's target is the module path of the call site. Key-value fields can be
+/// attached before the message, separated from it by a semicolon; values are anything
+/// that converts into a `FieldValue`.
+/// # Examples:
+/// ```
+/// use cli_utils::config::LogLevel;
+/// cli_utils::log!(LogLevel::Warn, "disk {}% full", 91);
+/// cli_utils::log!(LogLevel::Warn, mount = "/var", used = 0.91; "disk almost full");
+/// ```
+#[macro_export]
+macro_rules! log {
+    ($level:expr, $($key:ident = $value:expr),+ ; $($arg:tt)+) => {
+        $crate::config::log_with(
+            $level,
+            module_path!(),
+            format_args!($($arg)+),
+            &[$((stringify!($key), $crate::config::FieldValue::from($value))),+],
+        )
+    };
+    ($level:expr, $($arg:tt)+) => {
+        $crate::config::log_with($level, module_path!(), format_args!($($arg)+), &[])
+    };
+}
+
+/// Logs a message at `LogLevel::Debug`; see `log!` for attaching fields.
+#[macro_export]
+macro_rules! debug {
+    ($($arg:tt)+) => {
+        $crate::log!($crate::config::LogLevel::Debug, $($arg)+)
+    };
+}
+
+/// Logs a message at `LogLevel::Info`; see `log!` for attaching fields.
+#[macro_export]
+macro_rules! info {
+    ($($arg:tt)+) => {
+        $crate::log!($crate::config::LogLevel::Info, $($arg)+)
+    };
+}
+
+/// Logs a message at `LogLevel::Warn`; see `log!` for attaching fields.
+#[macro_export]
+macro_rules! warn {
+    ($($arg:tt)+) => {
+        $crate::log!($crate::config::LogLevel::Warn, $($arg)+)
+    };
+}
+
+/// Logs a message at `LogLevel::Error`; see `log!` for attaching fields.
+#[macro_export]
+macro_rules! error {
+    ($($arg:tt)+) => {
+        $crate::log!($crate::config::LogLevel::Error, $($arg)+)
+    };
+}
@@ -0,0 +1,350 @@
+//! An optional asynchronous mode for the logger: records are queued on a bounded channel
+//! and written in batches by a background thread.
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::thread::JoinHandle;
+
+/// What a logging call does when the queue of the non-blocking writer is full.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Overflow {
+    /// Wait until the writer thread makes room. No record is lost.
+    #[default]
+    Block,
+    /// Discard the record being logged.
+    DropNewest,
+    /// Discard the oldest queued record to make room for the new one.
+    DropOldest,
+}
+
+impl fmt::Display for Overflow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(match self {
+            Overflow::Block => "block",
+            Overflow::DropNewest => "drop-newest",
+            Overflow::DropOldest => "drop-oldest",
+        })
+    }
+}
+
+impl FromStr for Overflow {
+    type Err = ParseOverflowError;
+
+    /// Parses `block`, `drop-newest` or `drop-oldest`, case-insensitively.
+    /// Underscores may be used in place of dashes.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().replace('_', "-").as_str() {
+            "block" => Ok(Overflow::Block),
+            "drop-newest" => Ok(Overflow::DropNewest),
+            "drop-oldest" => Ok(Overflow::DropOldest),
+            _ => Err(ParseOverflowError(s.to_string())),
+        }
+    }
+}
+
+/// The error returned when a string is not an overflow policy.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseOverflowError(String);
+
+impl fmt::Display for ParseOverflowError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid overflow policy {:?}: expected one of block, drop-newest, drop-oldest",
+            self.0
+        )
+    }
+}
+
+impl std::error::Error for ParseOverflowError {}
+
+/// Settings for `Logging::init_nonblocking`.
+/// # Examples:
+/// ```
+/// use cli_utils::config::{NonBlocking, Overflow};
+/// let options = NonBlocking { capacity: 1024, overflow: Overflow::DropOldest, ..NonBlocking::new() };
+/// assert_eq!(options.batch_size, 128);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NonBlocking {
+    /// How many records may wait in the queue before `overflow` applies.
+    pub capacity: usize,
+    /// The most records the writer thread takes from the queue at once; the destination
+    /// is flushed after every batch.
+    pub batch_size: usize,
+    pub overflow: Overflow,
+}
+
+impl NonBlocking {
+    /// Creates settings with room for 8192 records, batches of 128 and `Overflow::Block`.
+    pub fn new() -> Self {
+        Self {
+            capacity: 8192,
+            batch_size: 128,
+            overflow: Overflow::Block,
+        }
+    }
+}
+
+impl Default for NonBlocking {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+struct State<T> {
+    items: VecDeque<T>,
+    /// True while the writer thread is writing a batch it has taken off the queue.
+    busy: bool,
+    closed: bool,
+}
+
+/// The bounded queue between logging calls and the writer thread.
+pub(super) struct Queue<T> {
+    state: Mutex<State<T>>,
+    /// Signalled when a record is queued or the queue is closed.
+    queued: Condvar,
+    /// Signalled when the writer thread takes a batch or finishes writing one.
+    drained: Condvar,
+    options: NonBlocking,
+    dropped: AtomicU64,
+}
+
+impl<T> Queue<T> {
+    pub(super) fn new(options: NonBlocking) -> Self {
+        Self {
+            state: Mutex::new(State {
+                items: VecDeque::with_capacity(options.capacity.min(1024)),
+                busy: false,
+                closed: false,
+            }),
+            queued: Condvar::new(),
+            drained: Condvar::new(),
+            options: NonBlocking {
+                capacity: options.capacity.max(1),
+                batch_size: options.batch_size.max(1),
+                ..options
+            },
+            dropped: AtomicU64::new(0),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State<T>> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Queues `item` according to the overflow policy.
+    ///
+    /// Once the queue is closed the item is handed back, so the caller can write it itself.
+    pub(super) fn push(&self, item: T) -> Result<(), T> {
+        let mut state = self.lock();
+        while !state.closed && state.items.len() >= self.options.capacity {
+            match self.options.overflow {
+                Overflow::Block => {
+                    state = self.drained.wait(state).unwrap_or_else(|e| e.into_inner());
+                }
+                Overflow::DropNewest => {
+                    self.dropped.fetch_add(1, Ordering::Relaxed);
+                    return Ok(());
+                }
+                Overflow::DropOldest => {
+                    state.items.pop_front();
+                    self.dropped.fetch_add(1, Ordering::Relaxed);
+                }
+            }
+        }
+        if state.closed {
+            return Err(item);
+        }
+        state.items.push_back(item);
+        drop(state);
+        self.queued.notify_one();
+        Ok(())
+    }
+
+    /// Takes up to a batch of items, waiting for one to arrive. Returns an empty batch
+    /// once the queue is closed and empty.
+    fn take_batch(&self) -> Vec<T> {
+        let mut state = self.lock();
+        while state.items.is_empty() && !state.closed {
+            state = self.queued.wait(state).unwrap_or_else(|e| e.into_inner());
+        }
+        let n = state.items.len().min(self.options.batch_size);
+        let batch: Vec<T> = state.items.drain(..n).collect();
+        state.busy = !batch.is_empty();
+        drop(state);
+        self.drained.notify_all();
+        batch
+    }
+
+    fn finish_batch(&self) {
+        self.lock().busy = false;
+        self.drained.notify_all();
+    }
+
+    /// Waits until every queued item has been written.
+    pub(super) fn wait_until_idle(&self) {
+        let mut state = self.lock();
+        while !state.items.is_empty() || state.busy {
+            state = self.drained.wait(state).unwrap_or_else(|e| e.into_inner());
+        }
+    }
+
+    fn close(&self) {
+        self.lock().closed = true;
+        self.queued.notify_all();
+        self.drained.notify_all();
+    }
+}
+
+/// Runs the writer thread's loop until the queue is closed and drained.
+pub(super) fn run_worker<T>(queue: &Queue<T>, mut write_batch: impl FnMut(&[T])) {
+    loop {
+        let batch = queue.take_batch();
+        if batch.is_empty() {
+            return;
+        }
+        write_batch(&batch);
+        queue.finish_batch();
+    }
+}
+
+/// What a guard needs from its queue, whatever kind of item the queue holds.
+trait Shutdown: Send + Sync {
+    fn close(&self);
+    fn dropped(&self) -> u64;
+    fn options(&self) -> NonBlocking;
+}
+
+impl<T: Send> Shutdown for Queue<T> {
+    fn close(&self) {
+        Queue::close(self);
+    }
+
+    fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    fn options(&self) -> NonBlocking {
+        self.options
+    }
+}
+
+/// Keeps the writer thread of `Logging::init_nonblocking` running.
+///
+/// Dropping the guard writes every record still queued, flushes the destination and
+/// stops the thread; records logged after that are written synchronously. Keep it alive
+/// until the end of `main`, e.g. with `let _guard = ...`; `let _ = ...` drops it at once.
+#[must_use = "dropping the guard stops the non-blocking writer"]
+pub struct LogGuard {
+    queue: Arc<dyn Shutdown>,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl LogGuard {
+    pub(super) fn new<T: Send + 'static>(queue: Arc<Queue<T>>, worker: JoinHandle<()>) -> Self {
+        Self {
+            queue,
+            worker: Some(worker),
+        }
+    }
+
+    /// Returns how many records have been discarded because the queue was full.
+    pub fn dropped(&self) -> u64 {
+        self.queue.dropped()
+    }
+}
+
+impl fmt::Debug for LogGuard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LogGuard")
+            .field("options", &self.queue.options())
+            .field("dropped", &self.dropped())
+            .finish()
+    }
+}
+
+impl Drop for LogGuard {
+    fn drop(&mut self) {
+        self.queue.close();
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn queue(capacity: usize, overflow: Overflow) -> Queue<String> {
+        Queue::new(NonBlocking {
+            capacity,
+            overflow,
+            ..NonBlocking::new()
+        })
+    }
+
+    fn queued(queue: &Queue<String>) -> Vec<String> {
+        queue.lock().items.iter().cloned().collect()
+    }
+
+    #[test]
+    fn test_drop_newest_keeps_the_queue() {
+        let queue = queue(2, Overflow::DropNewest);
+        for line in ["a", "b", "c"] {
+            queue.push(line.to_string()).unwrap();
+        }
+        assert_eq!(queued(&queue), ["a", "b"]);
+        assert_eq!(queue.dropped.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn test_drop_oldest_makes_room() {
+        let queue = queue(2, Overflow::DropOldest);
+        for line in ["a", "b", "c", "d"] {
+            queue.push(line.to_string()).unwrap();
+        }
+        assert_eq!(queued(&queue), ["c", "d"]);
+        assert_eq!(queue.dropped.load(Ordering::Relaxed), 2);
+    }
+
+    #[test]
+    fn test_closed_queue_hands_lines_back() {
+        let queue = queue(2, Overflow::Block);
+        queue.push("a".to_string()).unwrap();
+        queue.close();
+        assert_eq!(queue.push("b".to_string()), Err("b".to_string()));
+        let mut written = Vec::new();
+        run_worker(&queue, |batch| written.extend_from_slice(batch));
+        assert_eq!(written, ["a"]);
+    }
+
+    #[test]
+    fn test_batches_are_bounded() {
+        let queue: Queue<String> = Queue::new(NonBlocking {
+            batch_size: 2,
+            ..NonBlocking::new()
+        });
+        for line in ["a", "b", "c"] {
+            queue.push(line.to_string()).unwrap();
+        }
+        queue.close();
+        let mut batches = Vec::new();
+        run_worker(&queue, |batch| batches.push(batch.len()));
+        assert_eq!(batches, [2, 1]);
+    }
+
+    #[test]
+    fn test_parse_overflow() {
+        assert_eq!("Drop_Oldest".parse(), Ok(Overflow::DropOldest));
+        assert_eq!(
+            Overflow::DropNewest.to_string().parse(),
+            Ok(Overflow::DropNewest)
+        );
+        assert!("drop".parse::<Overflow>().is_err());
+    }
+}
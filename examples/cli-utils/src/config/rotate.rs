@@ -0,0 +1,470 @@
+//! Size- and time-based rotation for `LogOutput::File`.
+
+use super::time::DateTime;
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// When the current log file is moved aside and a fresh one started.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RotationPolicy {
+    /// The file grows forever.
+    #[default]
+    Never,
+    /// Rotate before a record would take the file past this many bytes.
+    MaxBytes(u64),
+    /// Rotate on the first record after midnight UTC.
+    Daily,
+    /// Rotate on the first record of every UTC hour.
+    Hourly,
+}
+
+impl RotationPolicy {
+    /// Returns the length of a time-based period in seconds.
+    fn period_secs(self) -> Option<u64> {
+        match self {
+            RotationPolicy::Daily => Some(86_400),
+            RotationPolicy::Hourly => Some(3_600),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for RotationPolicy {
+    type Err = ParseRotationError;
+
+    /// Parses `never`, `daily`, `hourly` or a size such as `500000`, `64KB`, `10MB` or `1GB`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lower = s.trim().to_ascii_lowercase();
+        match lower.as_str() {
+            "never" => return Ok(RotationPolicy::Never),
+            "daily" => return Ok(RotationPolicy::Daily),
+            "hourly" => return Ok(RotationPolicy::Hourly),
+            _ => {}
+        }
+        let (digits, multiplier) = [("gb", 1 << 30), ("mb", 1 << 20), ("kb", 1 << 10), ("b", 1)]
+            .into_iter()
+            .find_map(|(unit, multiplier)| lower.strip_suffix(unit).map(|d| (d, multiplier)))
+            .unwrap_or((lower.as_str(), 1));
+        match digits.trim().parse::<u64>() {
+            Ok(n) if n > 0 => Ok(RotationPolicy::MaxBytes(n.saturating_mul(multiplier))),
+            _ => Err(ParseRotationError(s.to_string())),
+        }
+    }
+}
+
+/// The error returned when a string is not a rotation policy.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseRotationError(String);
+
+impl fmt::Display for ParseRotationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid rotation {:?}: expected never, daily, hourly or a size like 10MB",
+            self.0
+        )
+    }
+}
+
+impl std::error::Error for ParseRotationError {}
+
+/// How rotated files are named.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RotationSuffix {
+    /// `app.log.1` is the newest rotated file, `app.log.2` the one before it, and so on.
+    #[default]
+    Numbered,
+    /// `app.log.2024-03-01` for daily rotation, `app.log.2024-03-01T09` for hourly rotation
+    /// and `app.log.2024-03-01T09-05-00` for size-based rotation.
+    Dated,
+}
+
+/// How a `LogOutput::File` destination is rotated.
+/// # Examples:
+/// ```
+/// use cli_utils::config::{Rotation, RotationPolicy, RotationSuffix};
+/// let rotation = Rotation {
+///     policy: "10MB".parse().unwrap(),
+///     keep: 5,
+///     ..Rotation::new()
+/// };
+/// assert_eq!(rotation.policy, RotationPolicy::MaxBytes(10 * 1024 * 1024));
+/// assert_eq!(rotation.suffix, RotationSuffix::Numbered);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rotation {
+    pub policy: RotationPolicy,
+    /// How many rotated files to keep; older ones are deleted.
+    pub keep: usize,
+    pub suffix: RotationSuffix,
+    /// Gzip rotated files, adding a `.gz` extension. Requires the `gzip` cargo feature.
+    pub compress: bool,
+}
+
+impl Rotation {
+    /// No rotation, keeping 7 files with numbered suffixes once a policy is set.
+    pub fn new() -> Self {
+        Self {
+            policy: RotationPolicy::Never,
+            keep: 7,
+            suffix: RotationSuffix::Numbered,
+            compress: false,
+        }
+    }
+}
+
+impl Default for Rotation {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// An append-only log file that rotates itself according to a `Rotation`.
+///
+/// It is not synchronized on its own; the logger keeps it behind a mutex, so rotation
+/// never races with another thread's write.
+pub(crate) struct RotatingFile {
+    path: PathBuf,
+    file: File,
+    size: u64,
+    /// The time-based period the current file belongs to.
+    period: u64,
+    rotation: Rotation,
+}
+
+impl RotatingFile {
+    pub fn open(path: &Path, rotation: Rotation) -> io::Result<Self> {
+        if rotation.compress && !cfg!(feature = "gzip") {
+            return Err(gzip_unsupported());
+        }
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        let metadata = file.metadata()?;
+        // A file left over from an earlier run belongs to the period it was last written in.
+        let modified = match metadata.len() {
+            0 => SystemTime::now(),
+            _ => metadata.modified().unwrap_or_else(|_| SystemTime::now()),
+        };
+        Ok(Self {
+            path: path.to_path_buf(),
+            file,
+            size: metadata.len(),
+            period: period_of(rotation.policy, modified),
+            rotation,
+        })
+    }
+
+    pub fn write_line(&mut self, line: &str) -> io::Result<()> {
+        self.write_line_at(line, SystemTime::now())
+    }
+
+    fn write_line_at(&mut self, line: &str, now: SystemTime) -> io::Result<()> {
+        let due = match self.rotation.policy {
+            RotationPolicy::Never => false,
+            RotationPolicy::MaxBytes(max) => self.size > 0 && self.size + line.len() as u64 > max,
+            policy => period_of(policy, now) != self.period,
+        };
+        if due {
+            self.rotate(now)?;
+        }
+        self.file.write_all(line.as_bytes())?;
+        self.size += line.len() as u64;
+        Ok(())
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+
+    fn rotate(&mut self, now: SystemTime) -> io::Result<()> {
+        self.file.flush()?;
+        if self.rotation.keep == 0 {
+            fs::remove_file(&self.path)?;
+        } else {
+            let rotated = match self.rotation.suffix {
+                RotationSuffix::Numbered => {
+                    self.shift_numbered()?;
+                    self.with_suffix("1")
+                }
+                RotationSuffix::Dated => self.free_dated_path(now),
+            };
+            fs::rename(&self.path, &rotated)?;
+            if self.rotation.compress {
+                compress(&rotated)?;
+            }
+            if self.rotation.suffix == RotationSuffix::Dated {
+                self.remove_old_dated()?;
+            }
+        }
+        self.file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        self.size = 0;
+        self.period = period_of(self.rotation.policy, now);
+        Ok(())
+    }
+
+    /// Returns `app.log.<suffix>`.
+    fn with_suffix(&self, suffix: &str) -> PathBuf {
+        let mut name = self.path.as_os_str().to_owned();
+        name.push(".");
+        name.push(suffix);
+        PathBuf::from(name)
+    }
+
+    /// Returns the name a rotated file ends up with: `path`, plus `.gz` when compressing.
+    fn final_name(&self, path: PathBuf) -> PathBuf {
+        if self.rotation.compress {
+            let mut name = path.into_os_string();
+            name.push(".gz");
+            PathBuf::from(name)
+        } else {
+            path
+        }
+    }
+
+    /// Moves `app.log.N` to `app.log.N+1`, dropping the files past `keep`.
+    fn shift_numbered(&self) -> io::Result<()> {
+        let keep = self.rotation.keep;
+        remove_if_exists(&self.final_name(self.with_suffix(&keep.to_string())))?;
+        for n in (1..keep).rev() {
+            let from = self.final_name(self.with_suffix(&n.to_string()));
+            if from.exists() {
+                fs::rename(
+                    from,
+                    self.final_name(self.with_suffix(&(n + 1).to_string())),
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns an unused dated name for the file being rotated out.
+    fn free_dated_path(&self, now: SystemTime) -> PathBuf {
+        let stamp = match self.rotation.policy.period_secs() {
+            Some(secs) => {
+                let start = UNIX_EPOCH + Duration::from_secs(self.period * secs);
+                let date = DateTime::from_system_time(start);
+                let day = format!("{:04}-{:02}-{:02}", date.year, date.month, date.day);
+                if secs == 3_600 {
+                    format!("{}T{:02}", day, date.hour)
+                } else {
+                    day
+                }
+            }
+            None => {
+                let date = DateTime::from_system_time(now);
+                format!(
+                    "{:04}-{:02}-{:02}T{:02}-{:02}-{:02}",
+                    date.year, date.month, date.day, date.hour, date.minute, date.second
+                )
+            }
+        };
+        let mut candidate = self.with_suffix(&stamp);
+        let mut n = 1;
+        while self.final_name(candidate.clone()).exists() {
+            candidate = self.with_suffix(&format!("{}.{}", stamp, n));
+            n += 1;
+        }
+        candidate
+    }
+
+    /// Deletes the oldest dated files beyond `keep`.
+    fn remove_old_dated(&self) -> io::Result<()> {
+        let dir = match self.path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir.to_path_buf(),
+            _ => PathBuf::from("."),
+        };
+        let Some(base) = self.path.file_name().and_then(|n| n.to_str()) else {
+            return Ok(());
+        };
+        let prefix = format!("{}.", base);
+        let mut rotated: Vec<String> = fs::read_dir(&dir)?
+            .filter_map(|entry| entry.ok()?.file_name().into_string().ok())
+            .filter(|name| {
+                name.strip_prefix(&prefix).is_some_and(|rest| {
+                    rest.starts_with(|c: char| c.is_ascii_digit()) && rest.contains('-')
+                })
+            })
+            .collect();
+        // Dated suffixes sort chronologically.
+        rotated.sort();
+        let excess = rotated.len().saturating_sub(self.rotation.keep);
+        for name in &rotated[..excess] {
+            fs::remove_file(dir.join(name))?;
+        }
+        Ok(())
+    }
+}
+
+fn period_of(policy: RotationPolicy, time: SystemTime) -> u64 {
+    match policy.period_secs() {
+        Some(secs) => {
+            time.duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs()
+                / secs
+        }
+        None => 0,
+    }
+}
+
+fn remove_if_exists(path: &Path) -> io::Result<()> {
+    match fs::remove_file(path) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+/// Replaces `path` with a gzipped `path.gz`.
+#[cfg(feature = "gzip")]
+fn compress(path: &Path) -> io::Result<()> {
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+
+    let mut gz_name = path.as_os_str().to_owned();
+    gz_name.push(".gz");
+    let mut input = File::open(path)?;
+    let mut encoder = GzEncoder::new(
+        File::create(PathBuf::from(gz_name))?,
+        Compression::default(),
+    );
+    io::copy(&mut input, &mut encoder)?;
+    encoder.finish()?;
+    fs::remove_file(path)
+}
+
+#[cfg(not(feature = "gzip"))]
+fn compress(_path: &Path) -> io::Result<()> {
+    Err(gzip_unsupported())
+}
+
+fn gzip_unsupported() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::Unsupported,
+        "compressing rotated logs requires the `gzip` cargo feature",
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("cli-utils-rotate-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn read(path: PathBuf) -> String {
+        fs::read_to_string(path).unwrap_or_default()
+    }
+
+    #[test]
+    fn test_size_rotation_shifts_numbered_files() {
+        let dir = temp_dir("size");
+        let path = dir.join("app.log");
+        let rotation = Rotation {
+            policy: RotationPolicy::MaxBytes(10),
+            keep: 2,
+            ..Rotation::new()
+        };
+        let mut file = RotatingFile::open(&path, rotation).unwrap();
+        for line in ["one\n", "two\n", "three\n", "four\n", "five\n", "six\n"] {
+            file.write_line(line).unwrap();
+        }
+        file.flush().unwrap();
+        assert_eq!(read(path.clone()), "six\n");
+        assert_eq!(read(dir.join("app.log.1")), "four\nfive\n");
+        assert_eq!(read(dir.join("app.log.2")), "three\n");
+        assert!(!dir.join("app.log.3").exists());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_hourly_rotation_uses_dated_suffixes_of_the_closed_period() {
+        let dir = temp_dir("hourly");
+        let path = dir.join("app.log");
+        let rotation = Rotation {
+            policy: RotationPolicy::Hourly,
+            keep: 1,
+            suffix: RotationSuffix::Dated,
+            ..Rotation::new()
+        };
+        let nine = UNIX_EPOCH + Duration::from_secs(1_709_283_900);
+        let mut file = RotatingFile::open(&path, rotation).unwrap();
+        file.period = period_of(RotationPolicy::Hourly, nine);
+        file.write_line_at("a\n", nine).unwrap();
+        file.write_line_at("b\n", nine + Duration::from_secs(60))
+            .unwrap();
+        file.write_line_at("c\n", nine + Duration::from_secs(3_600))
+            .unwrap();
+        file.write_line_at("d\n", nine + Duration::from_secs(7_200))
+            .unwrap();
+        file.flush().unwrap();
+
+        assert_eq!(read(path), "d\n");
+        assert!(!dir.join("app.log.2024-03-01T09").exists());
+        assert_eq!(read(dir.join("app.log.2024-03-01T10")), "c\n");
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_keep_zero_discards_old_records() {
+        let dir = temp_dir("keep0");
+        let path = dir.join("app.log");
+        let rotation = Rotation {
+            policy: RotationPolicy::MaxBytes(4),
+            keep: 0,
+            ..Rotation::new()
+        };
+        let mut file = RotatingFile::open(&path, rotation).unwrap();
+        file.write_line("old\n").unwrap();
+        file.write_line("new\n").unwrap();
+        assert_eq!(read(path), "new\n");
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_policy_parsing() {
+        assert_eq!("Daily".parse(), Ok(RotationPolicy::Daily));
+        assert_eq!("64KB".parse(), Ok(RotationPolicy::MaxBytes(65_536)));
+        assert_eq!("100".parse(), Ok(RotationPolicy::MaxBytes(100)));
+        assert!("0MB".parse::<RotationPolicy>().is_err());
+        assert!("weekly".parse::<RotationPolicy>().is_err());
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn test_rotated_files_are_gzipped() {
+        use flate2::read::GzDecoder;
+        use std::io::Read;
+
+        let dir = temp_dir("gzip");
+        let path = dir.join("app.log");
+        let rotation = Rotation {
+            policy: RotationPolicy::MaxBytes(4),
+            keep: 3,
+            compress: true,
+            ..Rotation::new()
+        };
+        let mut file = RotatingFile::open(&path, rotation).unwrap();
+        file.write_line("old\n").unwrap();
+        file.write_line("new\n").unwrap();
+
+        let mut unzipped = String::new();
+        GzDecoder::new(File::open(dir.join("app.log.1.gz")).unwrap())
+            .read_to_string(&mut unzipped)
+            .unwrap();
+        assert_eq!(unzipped, "old\n");
+        assert!(!dir.join("app.log.1").exists());
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,106 @@
+//! Encoding records as RFC 5424 syslog messages for the local syslog socket.
+
+#[cfg(unix)]
+use super::format::{write_logfmt_pair, FieldValue};
+#[cfg(unix)]
+use super::time::DateTime;
+#[cfg(unix)]
+use super::LogLevel;
+#[cfg(unix)]
+use std::fmt::Write;
+#[cfg(unix)]
+use std::sync::OnceLock;
+
+/// The socket `LogOutput::Syslog` connects to unless given another path.
+pub(crate) const DEFAULT_SOCKET: &str = "/dev/log";
+
+/// The `user` facility, for messages from ordinary programs.
+#[cfg(unix)]
+const FACILITY_USER: u8 = 1;
+
+/// Returns the name of the running program, as used for the syslog `APP-NAME` and the
+/// journal's `SYSLOG_IDENTIFIER`.
+#[cfg(unix)]
+pub(crate) fn app_name() -> &'static str {
+    static NAME: OnceLock<String> = OnceLock::new();
+    NAME.get_or_init(|| {
+        let name: String = std::env::args_os()
+            .next()
+            .as_deref()
+            .and_then(|arg| std::path::Path::new(arg).file_name())
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default()
+            .chars()
+            .filter(|c| c.is_ascii_graphic())
+            .take(48)
+            .collect();
+        if name.is_empty() {
+            "-".to_string()
+        } else {
+            name
+        }
+    })
+}
+
+/// Formats one record as an RFC 5424 message:
+/// `<14>1 2024-03-01T09:05:00.042Z - myapp 4242 - - [myapp::sync] done peers=3`.
+///
+/// The hostname is left for the syslog daemon to fill in; the target and the fields are
+/// written into the message the way `LogFormat::Text` writes them.
+#[cfg(unix)]
+pub(crate) fn encode<K: AsRef<str>>(
+    time: &DateTime,
+    level: LogLevel,
+    app_name: &str,
+    target: &str,
+    message: &str,
+    fields: &[(K, FieldValue)],
+) -> String {
+    let mut out = String::with_capacity(64 + message.len());
+    let _ = write!(
+        out,
+        "<{}>1 {} - {} {} - - [{}] {}",
+        FACILITY_USER * 8 + level.priority(),
+        time.rfc3339(),
+        app_name,
+        std::process::id(),
+        target,
+        message
+    );
+    for (key, value) in fields {
+        out.push(' ');
+        write_logfmt_pair(&mut out, key.as_ref(), &value.to_string());
+    }
+    out
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    #[test]
+    fn test_encode_maps_levels_to_priorities() {
+        let time =
+            DateTime::from_system_time(UNIX_EPOCH + Duration::from_millis(1_709_283_900_042));
+        let fields = [("peers", FieldValue::from(3))];
+        let message = encode(
+            &time,
+            LogLevel::Warn,
+            "myapp",
+            "myapp::sync",
+            "done",
+            &fields,
+        );
+        assert_eq!(
+            message,
+            format!(
+                "<12>1 2024-03-01T09:05:00.042Z - myapp {} - - [myapp::sync] done peers=3",
+                std::process::id()
+            )
+        );
+        let no_fields: [(&str, FieldValue); 0] = [];
+        let message = encode(&time, LogLevel::Debug, "-", "app", "x", &no_fields);
+        assert!(message.starts_with("<15>1 "));
+    }
+}
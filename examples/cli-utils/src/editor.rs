@@ -0,0 +1,897 @@
+//! Editing a line on the terminal, with history and emacs key bindings.
+
+#[cfg(unix)]
+use crate::complete::format_columns;
+use crate::complete::Completer;
+#[cfg(any(unix, test))]
+use crate::complete::{common_prefix, Candidate};
+#[cfg(any(unix, test))]
+use crate::keys::{Key, KeyEvent, Modifier, Modifiers};
+use crate::{_try_read_line, ReadError};
+use std::fmt;
+use std::fs;
+#[cfg(unix)]
+use std::io::IsTerminal;
+use std::io::{self, BufRead, Write};
+use std::path::Path;
+#[cfg(any(unix, test))]
+use unicode_width::UnicodeWidthChar;
+
+/// Previously entered lines, oldest first.
+///
+/// Empty lines and lines equal to the one before them are not added, and the oldest
+/// lines are dropped once there are more than the capacity.
+/// # Examples:
+/// ```
+/// use cli_utils::History;
+/// let mut history = History::new(2);
+/// history.add("ls");
+/// history.add("ls");
+/// history.add("cd src");
+/// history.add("make");
+/// assert_eq!(history.iter().collect::<Vec<_>>(), ["cd src", "make"]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct History {
+    entries: Vec<String>,
+    capacity: usize,
+}
+
+impl History {
+    /// Creates an empty history that keeps at most `capacity` lines.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            entries: Vec::new(),
+            capacity,
+        }
+    }
+
+    /// Adds `line` as the newest entry, returning whether it was added.
+    pub fn add(&mut self, line: &str) -> bool {
+        if line.trim().is_empty() || self.entries.last().is_some_and(|last| last == line) {
+            return false;
+        }
+        self.entries.push(line.to_string());
+        if self.entries.len() > self.capacity {
+            let excess = self.entries.len() - self.capacity;
+            self.entries.drain(..excess);
+        }
+        true
+    }
+
+    /// Returns the entry at `index`, counting from the oldest.
+    pub fn get(&self, index: usize) -> Option<&str> {
+        self.entries.get(index).map(String::as_str)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Iterates over the entries, oldest first.
+    pub fn iter(&self) -> impl Iterator<Item = &str> + '_ {
+        self.entries.iter().map(String::as_str)
+    }
+
+    /// Adds the lines of the file at `path`, one entry per line. A missing file is
+    /// treated as an empty one, so the first run of a program needs no special case.
+    /// # Errors:
+    /// Returns an error if the file exists but cannot be read.
+    pub fn load(&mut self, path: impl AsRef<Path>) -> io::Result<()> {
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+            Err(e) => return Err(e),
+        };
+        for line in text.lines() {
+            self.add(line);
+        }
+        Ok(())
+    }
+
+    /// Writes the entries to the file at `path`, one per line, replacing its contents.
+    /// # Errors:
+    /// Returns an error if the file cannot be written.
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let mut text = String::new();
+        for entry in &self.entries {
+            text.push_str(entry);
+            text.push('\n');
+        }
+        fs::write(path, text)
+    }
+
+    /// Returns the newest entry before `before` that contains `query`.
+    #[cfg(any(unix, test))]
+    fn search(&self, query: &str, before: usize) -> Option<usize> {
+        self.entries[..before.min(self.entries.len())]
+            .iter()
+            .rposition(|entry| entry.contains(query))
+    }
+}
+
+impl Default for History {
+    /// A history of up to 1000 lines.
+    fn default() -> Self {
+        Self::new(1000)
+    }
+}
+
+/// The text being edited and the cursor, as a position between characters.
+#[cfg(any(unix, test))]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct LineBuffer {
+    chars: Vec<char>,
+    cursor: usize,
+}
+
+#[cfg(any(unix, test))]
+fn is_word(c: char) -> bool {
+    c.is_alphanumeric()
+}
+
+#[cfg(any(unix, test))]
+impl LineBuffer {
+    /// A buffer holding `text` with the cursor at its end.
+    fn new(text: &str) -> Self {
+        let chars: Vec<char> = text.chars().collect();
+        let cursor = chars.len();
+        Self { chars, cursor }
+    }
+
+    fn text(&self) -> String {
+        self.chars.iter().collect()
+    }
+
+    fn insert(&mut self, text: &str) {
+        for c in text.chars() {
+            self.chars.insert(self.cursor, c);
+            self.cursor += 1;
+        }
+    }
+
+    /// The cursor as a byte offset into `text()`.
+    fn byte_cursor(&self) -> usize {
+        self.chars[..self.cursor].iter().map(|c| c.len_utf8()).sum()
+    }
+
+    /// Returns a copy with `candidate` in place of its range, and the cursor after it.
+    fn completed(&self, candidate: &Candidate) -> LineBuffer {
+        let text = self.text();
+        let start = text[..candidate.range.start].chars().count();
+        let end = text[..candidate.range.end].chars().count();
+        let mut line = self.clone();
+        line.chars.drain(start..end);
+        line.cursor = start;
+        line.insert(&candidate.replacement);
+        line
+    }
+
+    fn remove(&mut self, start: usize, end: usize) -> String {
+        self.cursor = start;
+        self.chars.drain(start..end).collect()
+    }
+
+    /// The start of the word before the cursor, where words are runs of letters and digits.
+    fn word_start(&self) -> usize {
+        let mut i = self.cursor;
+        while i > 0 && !is_word(self.chars[i - 1]) {
+            i -= 1;
+        }
+        while i > 0 && is_word(self.chars[i - 1]) {
+            i -= 1;
+        }
+        i
+    }
+
+    /// The end of the word after the cursor.
+    fn word_end(&self) -> usize {
+        let mut i = self.cursor;
+        while i < self.chars.len() && !is_word(self.chars[i]) {
+            i += 1;
+        }
+        while i < self.chars.len() && is_word(self.chars[i]) {
+            i += 1;
+        }
+        i
+    }
+
+    /// The start of the whitespace-separated word before the cursor, as Ctrl-W deletes it.
+    fn big_word_start(&self) -> usize {
+        let mut i = self.cursor;
+        while i > 0 && self.chars[i - 1].is_whitespace() {
+            i -= 1;
+        }
+        while i > 0 && !self.chars[i - 1].is_whitespace() {
+            i -= 1;
+        }
+        i
+    }
+
+    /// The width on screen of the characters in `start..end`.
+    fn width(&self, start: usize, end: usize) -> usize {
+        self.chars[start..end]
+            .iter()
+            .map(|c| c.width().unwrap_or(0))
+            .sum()
+    }
+}
+
+/// What happened to the line after a key press.
+#[cfg(any(unix, test))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Outcome {
+    Continue,
+    Done(String),
+    /// Ctrl-D on an empty line.
+    Eof,
+}
+
+/// An incremental search backwards through the history, started with Ctrl-R.
+#[cfg(any(unix, test))]
+#[derive(Debug)]
+struct Search {
+    query: String,
+    found: Option<usize>,
+    failed: bool,
+    /// The line and history position to go back to if the search is cancelled.
+    original: (LineBuffer, usize),
+}
+
+/// Going through the candidates of a completion with Tab and Shift-Tab.
+#[cfg(any(unix, test))]
+#[derive(Debug)]
+struct Cycle {
+    candidates: Vec<Candidate>,
+    current: usize,
+    /// The line before completion, which each candidate is applied to.
+    original: LineBuffer,
+}
+
+/// The state of a line being edited: the text, the place in the history, the last
+/// deleted text for Ctrl-Y, and a search or completion in progress.
+#[cfg(any(unix, test))]
+struct Editor<'a> {
+    line: LineBuffer,
+    history: &'a History,
+    completer: Option<&'a dyn Completer>,
+    /// The history entry being shown, or `history.len()` for the line being written.
+    index: usize,
+    draft: String,
+    killed: String,
+    search: Option<Search>,
+    cycle: Option<Cycle>,
+    /// Candidates to show below the line, taken by whoever draws it.
+    listing: Option<Vec<String>>,
+    /// The row the terminal cursor was left on, counted from the first row drawn.
+    cursor_row: usize,
+    /// The last row drawn, as the prompt and line may wrap onto several.
+    last_row: usize,
+}
+
+#[cfg(any(unix, test))]
+impl<'a> Editor<'a> {
+    fn new(history: &'a History, completer: Option<&'a dyn Completer>) -> Self {
+        Self {
+            line: LineBuffer::default(),
+            history,
+            completer,
+            index: history.len(),
+            draft: String::new(),
+            killed: String::new(),
+            search: None,
+            cycle: None,
+            listing: None,
+            cursor_row: 0,
+            last_row: 0,
+        }
+    }
+
+    /// Applies a key event to the line.
+    fn handle(&mut self, event: KeyEvent) -> Outcome {
+        if self.search.is_some() {
+            match self.handle_search(&event) {
+                Some(outcome) => return outcome,
+                None => self.search = None,
+            }
+        }
+        if self.cycle.is_some() {
+            if let Some(outcome) = self.handle_cycle(&event) {
+                return outcome;
+            }
+        }
+        let (key, modifiers) = match event {
+            KeyEvent::Key(key, modifiers) => (key, modifiers),
+            KeyEvent::Paste(text) => {
+                self.line.insert(&text.replace('\n', " "));
+                return Outcome::Continue;
+            }
+            KeyEvent::Unknown(_) => return Outcome::Continue,
+        };
+        let ctrl = modifiers == Modifiers::new().with(Modifier::Ctrl);
+        let alt = modifiers == Modifiers::new().with(Modifier::Alt);
+        let line = &mut self.line;
+        let end = line.chars.len();
+        match key {
+            Key::Enter => return Outcome::Done(line.text()),
+            Key::Char('d') if ctrl && end == 0 => return Outcome::Eof,
+            Key::Char('a') if ctrl => line.cursor = 0,
+            Key::Home => line.cursor = 0,
+            Key::Char('e') if ctrl => line.cursor = end,
+            Key::End => line.cursor = end,
+            Key::Char('b') if ctrl => line.cursor = line.cursor.saturating_sub(1),
+            Key::Char('f') if ctrl => line.cursor = (line.cursor + 1).min(end),
+            Key::Char('b') if alt => line.cursor = line.word_start(),
+            Key::Char('f') if alt => line.cursor = line.word_end(),
+            Key::Left if modifiers.contains(Modifier::Ctrl) || alt => {
+                line.cursor = line.word_start()
+            }
+            Key::Right if modifiers.contains(Modifier::Ctrl) || alt => {
+                line.cursor = line.word_end()
+            }
+            Key::Left => line.cursor = line.cursor.saturating_sub(1),
+            Key::Right => line.cursor = (line.cursor + 1).min(end),
+            Key::Backspace if alt => self.killed = line.remove(line.word_start(), line.cursor),
+            Key::Backspace if line.cursor > 0 => {
+                line.remove(line.cursor - 1, line.cursor);
+            }
+            Key::Delete if line.cursor < end => {
+                line.remove(line.cursor, line.cursor + 1);
+            }
+            Key::Char('d') if ctrl && line.cursor < end => {
+                line.remove(line.cursor, line.cursor + 1);
+            }
+            Key::Char('d') if alt => {
+                let start = line.cursor;
+                self.killed = line.remove(start, line.word_end());
+            }
+            Key::Char('k') if ctrl => self.killed = line.remove(line.cursor, end),
+            Key::Char('u') if ctrl => self.killed = line.remove(0, line.cursor),
+            Key::Char('w') if ctrl => self.killed = line.remove(line.big_word_start(), line.cursor),
+            Key::Char('y') if ctrl => line.insert(&self.killed),
+            Key::Tab if modifiers.is_empty() => self.complete(),
+            Key::Up => self.recall_older(),
+            Key::Char('p') if ctrl => self.recall_older(),
+            Key::Down => self.recall_newer(),
+            Key::Char('n') if ctrl => self.recall_newer(),
+            Key::Char('r') if ctrl => {
+                self.search = Some(Search {
+                    query: String::new(),
+                    found: None,
+                    failed: false,
+                    original: (line.clone(), self.index),
+                })
+            }
+            Key::Char(c)
+                if modifiers.is_empty() || modifiers == Modifiers::new().with(Modifier::Shift) =>
+            {
+                line.insert(c.encode_utf8(&mut [0; 4]))
+            }
+            _ => {}
+        }
+        Outcome::Continue
+    }
+
+    /// Handles a key during a search, returning `None` when the key ends the search and
+    /// should be handled as usual on the line it found.
+    fn handle_search(&mut self, event: &KeyEvent) -> Option<Outcome> {
+        let search = self.search.as_mut()?;
+        let KeyEvent::Key(key, modifiers) = event else {
+            return None;
+        };
+        let ctrl = *modifiers == Modifiers::new().with(Modifier::Ctrl);
+        let before = match key {
+            Key::Char('r') if ctrl => search.found.unwrap_or(self.history.len()),
+            Key::Char('g') if ctrl => return Some(self.cancel_search()),
+            Key::Escape => return Some(self.cancel_search()),
+            Key::Backspace => {
+                search.query.pop();
+                self.history.len()
+            }
+            Key::Char(c) if modifiers.is_empty() => {
+                search.query.push(*c);
+                search.found.map_or(self.history.len(), |i| i + 1)
+            }
+            _ => return None,
+        };
+        match self.history.search(&search.query, before) {
+            Some(found) if !search.query.is_empty() => {
+                search.found = Some(found);
+                search.failed = false;
+                let entry = self.history.get(found).unwrap_or_default();
+                let at = entry.find(&search.query).unwrap_or(0);
+                self.index = found;
+                self.line = LineBuffer::new(entry);
+                self.line.cursor = entry[..at].chars().count();
+            }
+            _ => search.failed = !search.query.is_empty(),
+        }
+        Some(Outcome::Continue)
+    }
+
+    /// Completes the word under the cursor. A prefix shared by all the candidates is
+    /// inserted if there is one; otherwise the candidates are listed and the first one is
+    /// put in, starting a cycle through them.
+    fn complete(&mut self) {
+        let Some(completer) = self.completer else {
+            return;
+        };
+        let text = self.line.text();
+        let mut candidates = completer.complete(&text, self.line.byte_cursor());
+        candidates.retain(|c| text.get(c.range.clone()).is_some());
+        if candidates.len() == 1 {
+            self.line = self.line.completed(&candidates[0]);
+            return;
+        }
+        let Some(first) = candidates.first() else {
+            return;
+        };
+        let range = first.range.clone();
+        let typed = &text[range.clone()];
+        let prefix = common_prefix(candidates.iter().map(|c| c.replacement.as_str()));
+        if candidates.iter().all(|c| c.range == range)
+            && prefix.len() > typed.len()
+            && prefix.starts_with(typed)
+        {
+            self.line = self.line.completed(&Candidate::new(range, prefix));
+            return;
+        }
+        self.listing = Some(candidates.iter().map(|c| c.display.clone()).collect());
+        let original = self.line.clone();
+        self.line = original.completed(first);
+        self.cycle = Some(Cycle {
+            candidates,
+            current: 0,
+            original,
+        });
+    }
+
+    /// Handles a key while cycling through candidates, returning `None` when the key
+    /// keeps the current candidate and should be handled as usual.
+    fn handle_cycle(&mut self, event: &KeyEvent) -> Option<Outcome> {
+        let cycle = self.cycle.as_mut()?;
+        let count = cycle.candidates.len();
+        let step = match event {
+            KeyEvent::Key(Key::Tab, modifiers) if modifiers.is_empty() => 1,
+            KeyEvent::Key(Key::Tab, modifiers) if modifiers.contains(Modifier::Shift) => count - 1,
+            KeyEvent::Key(Key::Escape, _) => {
+                self.line = cycle.original.clone();
+                self.cycle = None;
+                return Some(Outcome::Continue);
+            }
+            _ => {
+                self.cycle = None;
+                return None;
+            }
+        };
+        cycle.current = (cycle.current + step) % count;
+        self.line = cycle.original.completed(&cycle.candidates[cycle.current]);
+        Some(Outcome::Continue)
+    }
+
+    fn cancel_search(&mut self) -> Outcome {
+        if let Some(search) = self.search.take() {
+            (self.line, self.index) = search.original;
+        }
+        Outcome::Continue
+    }
+
+    fn recall(&mut self, index: usize) {
+        if self.index == self.history.len() {
+            self.draft = self.line.text();
+        }
+        self.index = index;
+        self.line = match self.history.get(index) {
+            Some(entry) => LineBuffer::new(entry),
+            None => LineBuffer::new(&self.draft),
+        };
+    }
+
+    fn recall_older(&mut self) {
+        if self.index > 0 {
+            self.recall(self.index - 1);
+        }
+    }
+
+    fn recall_newer(&mut self) {
+        if self.index < self.history.len() {
+            self.recall(self.index + 1);
+        }
+    }
+
+    /// Redraws the line after `prompt` on a terminal `width` columns wide, leaving the
+    /// terminal cursor at the line's cursor. Rows the line wraps onto are cleared along
+    /// with it, so a line that got shorter leaves nothing behind.
+    fn render(&mut self, prompt: &str, width: usize) -> String {
+        let shown = match &self.search {
+            Some(search) => format!(
+                "({}reverse-i-search)`{}': ",
+                if search.failed { "failed " } else { "" },
+                search.query
+            ),
+            None => prompt.to_string(),
+        };
+        let width = width.max(1);
+        let mut out = String::new();
+        if self.cursor_row > 0 {
+            out.push_str(&format!("\x1b[{}A", self.cursor_row));
+        }
+        out.push('\r');
+        out.push_str(&shown);
+        out.push_str(&self.line.text());
+        let start = crate::colors::display_width(&shown);
+        let end = start + self.line.width(0, self.line.chars.len());
+        // A terminal holds the cursor on the last column of a full row until something
+        // more is written, so move to the next row to clear from there.
+        if end > 0 && end.is_multiple_of(width) {
+            out.push_str("\r\n");
+        }
+        out.push_str("\x1b[J");
+        let at = start + self.line.width(0, self.line.cursor);
+        let (row, column) = (at / width, at % width);
+        let last_row = end / width;
+        if row == last_row {
+            let back = end % width - column;
+            if back > 0 {
+                out.push_str(&format!("\x1b[{}D", back));
+            }
+        } else {
+            out.push_str(&format!("\x1b[{}A\r", last_row - row));
+            if column > 0 {
+                out.push_str(&format!("\x1b[{}C", column));
+            }
+        }
+        self.cursor_row = row;
+        self.last_row = last_row;
+        out
+    }
+
+    /// Moves the terminal cursor below the line drawn last, so that the next render
+    /// starts a new one.
+    fn leave(&mut self) -> String {
+        let down = self.last_row - self.cursor_row;
+        self.cursor_row = 0;
+        self.last_row = 0;
+        if down > 0 {
+            format!("\x1b[{}B\n", down)
+        } else {
+            "\n".to_string()
+        }
+    }
+}
+
+/// Reads lines from the terminal with editing and history, like a shell does.
+///
+/// The cursor moves with the arrow keys, Home and End, Ctrl-A, Ctrl-E, Ctrl-B and Ctrl-F,
+/// and by words with Alt-B, Alt-F or Ctrl and the arrows. Ctrl-K, Ctrl-U and Ctrl-W delete
+/// to the end of the line, to its start and the word before the cursor, and Ctrl-Y puts
+/// the deleted text back. Up and down, or Ctrl-P and Ctrl-N, go through the history and
+/// Ctrl-R searches it. Ctrl-D on an empty line ends the input.
+///
+/// With a completer, Tab completes the word under the cursor: it fills in what all the
+/// candidates have in common, or else lists them and puts in each in turn on every
+/// further Tab. Shift-Tab goes back and Escape returns to what was typed.
+///
+/// When stdin or stdout is not a terminal, the prompt is written and a line is read as
+/// `try_read_line` reads it.
+/// # Examples:
+/// ```no_run
+/// use cli_utils::LineEditor;
+/// let mut editor = LineEditor::new();
+/// editor.history_mut().load(".calc_history").unwrap();
+/// while let Ok(line) = editor.read_line("> ") {
+///     println!("{}", line);
+/// }
+/// editor.history().save(".calc_history").unwrap();
+/// ```
+#[derive(Default)]
+pub struct LineEditor {
+    history: History,
+    completer: Option<Box<dyn Completer>>,
+}
+
+impl LineEditor {
+    /// Creates an editor with an empty history of up to 1000 lines.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Uses `history` instead of an empty one.
+    pub fn with_history(mut self, history: History) -> Self {
+        self.history = history;
+        self
+    }
+
+    /// Completes words with `completer` when Tab is pressed.
+    /// # Examples:
+    /// ```no_run
+    /// use cli_utils::{LineEditor, PathCompleter};
+    /// let mut editor = LineEditor::new().completer(PathCompleter::new());
+    /// let path = editor.read_line("File: ").unwrap();
+    /// ```
+    pub fn completer(mut self, completer: impl Completer + 'static) -> Self {
+        self.completer = Some(Box::new(completer));
+        self
+    }
+
+    pub fn history(&self) -> &History {
+        &self.history
+    }
+
+    pub fn history_mut(&mut self) -> &mut History {
+        &mut self.history
+    }
+
+    /// Writes `prompt` to stdout and reads a line from stdin, adding it to the history.
+    /// # Errors:
+    /// Returns `ReadError::Eof` at the end of input or on Ctrl-D on an empty line, and
+    /// `ReadError::Io` if the terminal cannot be read or written.
+    pub fn read_line(&mut self, prompt: &str) -> Result<String, ReadError> {
+        let stdin = io::stdin();
+        #[cfg(unix)]
+        if stdin.is_terminal() && io::stdout().is_terminal() {
+            let line = edit_line(prompt, &self.history, self.completer.as_deref())?;
+            self.history.add(&line);
+            return Ok(line);
+        }
+        self.read_from(&mut stdin.lock(), &mut io::stdout(), prompt)
+    }
+
+    /// Reads a line without editing, writing `prompt` to `writer` first.
+    fn read_from<R: BufRead, W: Write>(
+        &mut self,
+        reader: &mut R,
+        writer: &mut W,
+        prompt: &str,
+    ) -> Result<String, ReadError> {
+        writer.write_all(prompt.as_bytes())?;
+        writer.flush()?;
+        let line = _try_read_line(reader)?;
+        self.history.add(&line);
+        Ok(line)
+    }
+}
+
+/// Edits a line on the terminal with `history` to recall from and `completer` for Tab,
+/// returning it once Enter is pressed.
+#[cfg(unix)]
+pub(crate) fn edit_line(
+    prompt: &str,
+    history: &History,
+    completer: Option<&dyn Completer>,
+) -> Result<String, ReadError> {
+    let mut keys = crate::keys::KeyReader::new()?;
+    let mut stdout = io::stdout();
+    let mut editor = Editor::new(history, completer);
+    let fd = std::os::unix::io::AsRawFd::as_raw_fd(&stdout);
+    let width = || crate::term::columns(fd).unwrap_or(80);
+    let mut write = |s: &str| -> io::Result<()> {
+        stdout.write_all(s.as_bytes())?;
+        stdout.flush()
+    };
+    write(&editor.render(prompt, width()))?;
+    loop {
+        let outcome = editor.handle(keys.read_key()?);
+        if let Some(listing) = editor.listing.take() {
+            let items: Vec<&str> = listing.iter().map(String::as_str).collect();
+            write(&editor.leave())?;
+            write(&format_columns(&items, width()))?;
+        }
+        match outcome {
+            Outcome::Continue => write(&editor.render(prompt, width()))?,
+            Outcome::Done(line) => {
+                editor.search = None;
+                write(&editor.render(prompt, width()))?;
+                write(&editor.leave())?;
+                return Ok(line);
+            }
+            Outcome::Eof => {
+                write(&editor.leave())?;
+                return Err(ReadError::Eof);
+            }
+        }
+    }
+}
+
+impl fmt::Debug for LineEditor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LineEditor")
+            .field("history", &self.history)
+            .field("completer", &self.completer.is_some())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::keys::decode_key;
+    use std::io::Cursor;
+
+    /// Feeds `input` to an editor, returning the last outcome and the editor.
+    fn type_keys<'a>(history: &'a History, input: &[u8]) -> (Outcome, Editor<'a>) {
+        type_keys_with(history, None, input)
+    }
+
+    fn type_keys_with<'a>(
+        history: &'a History,
+        completer: Option<&'a dyn Completer>,
+        mut input: &[u8],
+    ) -> (Outcome, Editor<'a>) {
+        let mut editor = Editor::new(history, completer);
+        let mut outcome = Outcome::Continue;
+        while let Some((event, n)) = decode_key(input) {
+            outcome = editor.handle(event);
+            input = &input[n..];
+        }
+        assert!(input.is_empty(), "left over: {:?}", input);
+        (outcome, editor)
+    }
+
+    fn history(entries: &[&str]) -> History {
+        let mut history = History::default();
+        for entry in entries {
+            history.add(entry);
+        }
+        history
+    }
+
+    #[test]
+    fn test_cursor_movement_and_insertion() {
+        let empty = History::default();
+        let (outcome, _) = type_keys(&empty, b"wrld\x1b[D\x1b[D\x1b[Do\x01h\x05!\r");
+        assert_eq!(outcome, Outcome::Done("hworld!".to_string()));
+        let (_, editor) = type_keys(&empty, b"one two-three\x1bb\x1bb");
+        assert_eq!(editor.line.cursor, 4);
+        let (_, mut editor) = type_keys(&empty, b"one two\x01\x1b[1;5C\x1bf");
+        assert_eq!(editor.line.cursor, 7);
+        assert_eq!(editor.render("> ", 80), "\r> one two\x1b[J");
+        let (_, mut editor) = type_keys(&empty, "日本\x1b[D".as_bytes());
+        assert_eq!(editor.render("> ", 80), "\r> 日本\x1b[J\x1b[2D");
+    }
+
+    #[test]
+    fn test_render_tracks_wrapped_rows() {
+        let empty = History::default();
+        let (_, mut editor) = type_keys(&empty, b"abcdefgh\x01\x06");
+        assert_eq!(editor.render("> ", 4), "\r> abcdefgh\x1b[J\x1b[2A\r\x1b[3C");
+        assert_eq!((editor.cursor_row, editor.last_row), (0, 2));
+        editor.handle(KeyEvent::plain(Key::End));
+        assert_eq!(editor.render("> ", 4), "\r> abcdefgh\x1b[J");
+        assert_eq!((editor.cursor_row, editor.last_row), (2, 2));
+        editor.handle(KeyEvent::plain(Key::Backspace));
+        editor.handle(KeyEvent::plain(Key::Backspace));
+        assert_eq!(editor.render("> ", 4), "\x1b[2A\r> abcdef\r\n\x1b[J");
+        assert_eq!(editor.leave(), "\n");
+        assert_eq!(editor.render("> ", 4), "\r> abcdef\r\n\x1b[J");
+    }
+
+    #[test]
+    fn test_deleting_and_yanking() {
+        let empty = History::default();
+        let (_, editor) = type_keys(&empty, b"git commit -m\x17");
+        assert_eq!(editor.line.text(), "git commit ");
+        let (_, editor) = type_keys(&empty, b"abc def\x1b[D\x1b[D\x0b\x01\x19");
+        assert_eq!(editor.line.text(), "efabc d");
+        let (_, editor) = type_keys(&empty, b"abc def\x1b[D\x15");
+        assert_eq!(editor.line.text(), "f");
+        let (_, editor) = type_keys(&empty, b"ab\x7f\x01\x04 foo.bar\x1b\x7f");
+        assert_eq!(editor.line.text(), " foo.");
+        assert_eq!(type_keys(&empty, b"\x04").0, Outcome::Eof);
+    }
+
+    #[test]
+    fn test_history_navigation_keeps_the_draft() {
+        let history = history(&["first", "second"]);
+        let (_, editor) = type_keys(&history, b"dra\x1b[A");
+        assert_eq!(editor.line.text(), "second");
+        let (_, editor) = type_keys(&history, b"dra\x1b[A\x1b[A\x1b[A");
+        assert_eq!(editor.line.text(), "first");
+        let (outcome, _) = type_keys(&history, b"dra\x1b[A\x1b[A\x1b[B\x1b[Bft\r");
+        assert_eq!(outcome, Outcome::Done("draft".to_string()));
+    }
+
+    #[test]
+    fn test_reverse_search() {
+        let history = history(&["cargo build", "git status", "cargo test", "ls"]);
+        let (_, mut editor) = type_keys(&history, b"\x12car");
+        assert_eq!(editor.line.text(), "cargo test");
+        assert_eq!(
+            editor.render("> ", 80),
+            "\r(reverse-i-search)`car': cargo test\x1b[J\x1b[10D"
+        );
+        let (outcome, _) = type_keys(&history, b"\x12car\x12\r");
+        assert_eq!(outcome, Outcome::Done("cargo build".to_string()));
+        let (_, mut editor) = type_keys(&history, b"\x12cargo\x12\x12");
+        assert_eq!(editor.line.text(), "cargo build");
+        assert!(editor.render("", 80).contains("failed reverse-i-search"));
+        let (outcome, _) = type_keys(&history, b"\x12stat\x05!\r");
+        assert_eq!(outcome, Outcome::Done("git status!".to_string()));
+        let (_, mut editor) = type_keys(&history, b"x\x12zzz\x07");
+        assert_eq!(editor.render("> ", 80), "\r> x\x1b[J");
+        let (_, mut editor) = type_keys(&history, b"x\x12git");
+        editor.handle(KeyEvent::plain(Key::Escape));
+        assert_eq!((editor.line.text(), editor.index), ("x".to_string(), 4));
+    }
+
+    #[test]
+    fn test_history_skips_blank_and_repeated_lines() {
+        let mut history = History::new(3);
+        assert!(history.add("a"));
+        assert!(!history.add("a"));
+        assert!(!history.add("  "));
+        for line in ["b", "c", "d"] {
+            history.add(line);
+        }
+        assert_eq!(history.iter().collect::<Vec<_>>(), ["b", "c", "d"]);
+    }
+
+    #[test]
+    fn test_plain_fallback_reads_trimmed_lines_into_the_history() {
+        let mut editor = LineEditor::new();
+        let mut input = Cursor::new(" one \n");
+        let mut output = Vec::new();
+        assert_eq!(
+            editor.read_from(&mut input, &mut output, "> ").unwrap(),
+            "one"
+        );
+        assert!(editor
+            .read_from(&mut input, &mut output, "> ")
+            .unwrap_err()
+            .is_eof());
+        assert_eq!(output, b"> > ");
+        assert_eq!(editor.history().get(0), Some("one"));
+    }
+
+    #[test]
+    fn test_tab_completes_the_common_prefix_then_cycles() {
+        let empty = History::default();
+        let commands = |line: &str, cursor: usize| {
+            let start = line[..cursor].rfind(' ').map_or(0, |i| i + 1);
+            ["status", "stash", "show", "push"]
+                .iter()
+                .filter(|name| name.starts_with(&line[start..cursor]))
+                .map(|name| Candidate::new(start..cursor, *name))
+                .collect::<Vec<_>>()
+        };
+        let completer: Option<&dyn Completer> = Some(&commands);
+
+        let (_, editor) = type_keys_with(&empty, completer, b"git pu\t");
+        assert_eq!(editor.line.text(), "git push");
+        assert!(editor.listing.is_none());
+
+        let (_, editor) = type_keys_with(&empty, completer, b"git sta\t");
+        assert_eq!(editor.listing.unwrap(), ["status", "stash"]);
+        assert_eq!(editor.line.text(), "git status");
+
+        let (_, editor) = type_keys_with(&empty, completer, b"git s\t");
+        assert_eq!(editor.line.text(), "git status");
+        let (_, editor) = type_keys_with(&empty, completer, b"git s\t\t\t");
+        assert_eq!(editor.line.text(), "git show");
+        let (_, editor) = type_keys_with(&empty, completer, b"git s\t\x1b[Z");
+        assert_eq!(editor.line.text(), "git show");
+        let (outcome, _) = type_keys_with(&empty, completer, b"git s\t\t -a\r");
+        assert_eq!(outcome, Outcome::Done("git stash -a".to_string()));
+        let (_, mut editor) = type_keys_with(&empty, completer, b"git s\t\t");
+        editor.handle(KeyEvent::plain(Key::Escape));
+        assert_eq!(editor.line.text(), "git s");
+    }
+
+    #[test]
+    fn test_completion_replaces_the_range_before_the_cursor() {
+        let empty = History::default();
+        let upper = |line: &str, cursor: usize| {
+            vec![Candidate::new(0..cursor, line[..cursor].to_uppercase())]
+        };
+        let (_, editor) = type_keys_with(&empty, Some(&upper), "été rest\x01\x1bf\t".as_bytes());
+        assert_eq!(editor.line.text(), "ÉTÉ rest");
+        assert_eq!(editor.line.cursor, 3);
+        let outside = |_: &str, _: usize| vec![Candidate::new(0..99, "x")];
+        let (_, editor) = type_keys_with(&empty, Some(&outside), b"abc\t");
+        assert_eq!(editor.line.text(), "abc");
+    }
+}
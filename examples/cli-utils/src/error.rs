@@ -0,0 +1,61 @@
+//! Errors returned when reading input.
+
+use std::fmt;
+use std::io;
+use std::string::FromUtf8Error;
+
+/// The error returned by `try_read_line` and the other fallible readers.
+/// # Examples:
+/// ```
+/// use cli_utils::ReadError;
+/// let error = ReadError::Eof;
+/// assert_eq!(error.to_string(), "unexpected end of input");
+/// ```
+#[derive(Debug)]
+pub enum ReadError {
+    /// The input ended before a line could be read.
+    Eof,
+    /// Reading failed, for example because a pipe was closed.
+    Io(io::Error),
+    /// The line is not valid UTF-8. The raw bytes can be recovered with `into_bytes`.
+    InvalidUtf8(FromUtf8Error),
+}
+
+impl ReadError {
+    /// Returns true if the input ended.
+    pub fn is_eof(&self) -> bool {
+        matches!(self, ReadError::Eof)
+    }
+}
+
+impl fmt::Display for ReadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReadError::Eof => f.write_str("unexpected end of input"),
+            ReadError::Io(e) => write!(f, "failed to read input: {}", e),
+            ReadError::InvalidUtf8(e) => write!(f, "input is not valid UTF-8: {}", e.utf8_error()),
+        }
+    }
+}
+
+impl std::error::Error for ReadError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ReadError::Eof => None,
+            ReadError::Io(e) => Some(e),
+            ReadError::InvalidUtf8(e) => Some(e),
+        }
+    }
+}
+
+impl From<io::Error> for ReadError {
+    fn from(e: io::Error) -> Self {
+        ReadError::Io(e)
+    }
+}
+
+impl From<FromUtf8Error> for ReadError {
+    fn from(e: FromUtf8Error) -> Self {
+        ReadError::InvalidUtf8(e)
+    }
+}
@@ -0,0 +1,671 @@
+//! Reading key presses from a terminal in raw mode.
+//!
+//! `decode_key` turns the bytes a terminal sends into a `KeyEvent` without doing any I/O,
+//! so it can be used on input from anywhere. On unix, `RawMode` switches stdin to raw
+//! mode and `KeyReader` reads one `KeyEvent` at a time from it.
+//! # Examples:
+//! ```
+//! use cli_utils::keys::{decode_key, Key, KeyEvent, Modifier, Modifiers};
+//! let (event, used) = decode_key(b"\x1b[1;5Cabc").unwrap();
+//! assert_eq!(event, KeyEvent::Key(Key::Right, Modifiers::new().with(Modifier::Ctrl)));
+//! assert_eq!(used, 6);
+//! ```
+
+use std::fmt;
+
+/// A key on the keyboard.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Key {
+    Char(char),
+    Enter,
+    Tab,
+    Backspace,
+    Delete,
+    Insert,
+    Escape,
+    Up,
+    Down,
+    Left,
+    Right,
+    Home,
+    End,
+    PageUp,
+    PageDown,
+    /// A function key, from `F(1)` to `F(12)`.
+    F(u8),
+}
+
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Key::Char(' ') => write!(f, "Space"),
+            Key::Char(c) => write!(f, "{}", c),
+            Key::F(n) => write!(f, "F{}", n),
+            key => write!(f, "{:?}", key),
+        }
+    }
+}
+
+/// A key held down together with another key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Modifier {
+    Shift,
+    Alt,
+    Ctrl,
+}
+
+impl Modifier {
+    const ALL: [Modifier; 3] = [Modifier::Ctrl, Modifier::Alt, Modifier::Shift];
+
+    fn bit(self) -> u8 {
+        match self {
+            Modifier::Shift => 1,
+            Modifier::Alt => 2,
+            Modifier::Ctrl => 4,
+        }
+    }
+}
+
+/// A set of `Modifier`s.
+/// # Examples:
+/// ```
+/// use cli_utils::keys::{Modifier, Modifiers};
+/// let mods = Modifiers::new().with(Modifier::Ctrl);
+/// assert!(mods.contains(Modifier::Ctrl));
+/// assert!(!mods.contains(Modifier::Alt));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Modifiers(u8);
+
+impl Modifiers {
+    /// Creates an empty set.
+    pub fn new() -> Self {
+        Self(0)
+    }
+
+    /// Returns the set with `modifier` added.
+    pub fn with(self, modifier: Modifier) -> Self {
+        Self(self.0 | modifier.bit())
+    }
+
+    /// Adds `modifier` to the set.
+    pub fn insert(&mut self, modifier: Modifier) {
+        self.0 |= modifier.bit();
+    }
+
+    pub fn contains(&self, modifier: Modifier) -> bool {
+        self.0 & modifier.bit() != 0
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// Builds the set from the modifier parameter of an escape sequence, which is one more
+    /// than a bit mask of shift (1), alt (2) and ctrl (4).
+    fn from_param(param: u32) -> Self {
+        Self((param.saturating_sub(1) & 7) as u8)
+    }
+}
+
+/// What a terminal sent: a key press, pasted text, or a sequence that was not understood.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KeyEvent {
+    Key(Key, Modifiers),
+    /// Text pasted while bracketed paste was on, with line endings turned into `\n`.
+    Paste(String),
+    Unknown(Vec<u8>),
+}
+
+impl KeyEvent {
+    /// A press of `key` with no modifiers.
+    pub fn plain(key: Key) -> Self {
+        KeyEvent::Key(key, Modifiers::new())
+    }
+
+    fn with(self, modifier: Modifier) -> Self {
+        match self {
+            KeyEvent::Key(key, modifiers) => KeyEvent::Key(key, modifiers.with(modifier)),
+            event => event,
+        }
+    }
+}
+
+impl fmt::Display for KeyEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KeyEvent::Key(key, modifiers) => {
+                for modifier in Modifier::ALL {
+                    if modifiers.contains(modifier) {
+                        write!(f, "{:?}-", modifier)?;
+                    }
+                }
+                write!(f, "{}", key)
+            }
+            KeyEvent::Paste(text) => write!(f, "paste {:?}", text),
+            KeyEvent::Unknown(bytes) => write!(f, "unknown {:?}", bytes),
+        }
+    }
+}
+
+const PASTE_START: &[u8] = b"\x1b[200~";
+const PASTE_END: &[u8] = b"\x1b[201~";
+
+/// Decodes the first key event in `input`, returning it with the number of bytes it used.
+///
+/// Returns `None` if `input` is empty or ends in the middle of an event, in which case
+/// more input is needed. Since Escape is also the start of longer sequences, a lone
+/// `\x1b` is incomplete too: a reader decides it was the Escape key when nothing follows
+/// it for a while.
+///
+/// Ctrl with a letter arrives as `Key::Char` of the lowercase letter with `Modifier::Ctrl`,
+/// except Ctrl-H, Ctrl-I, Ctrl-J and Ctrl-M, which terminals send as Backspace, Tab and
+/// Enter. Alt arrives as an Escape before the key.
+/// # Examples:
+/// ```
+/// use cli_utils::keys::{decode_key, Key, KeyEvent, Modifier, Modifiers};
+/// assert_eq!(decode_key(b"\x01"), Some((KeyEvent::Key(Key::Char('a'), Modifiers::new().with(Modifier::Ctrl)), 1)));
+/// assert_eq!(decode_key(b"\x1b[6~"), Some((KeyEvent::plain(Key::PageDown), 4)));
+/// assert_eq!(decode_key(b"\x1b[200~hi\x1b[201~"), Some((KeyEvent::Paste("hi".to_string()), 14)));
+/// assert_eq!(decode_key(b"\x1b[1;"), None);
+/// ```
+pub fn decode_key(input: &[u8]) -> Option<(KeyEvent, usize)> {
+    match input.first()? {
+        0x1b => decode_escape(input),
+        _ => decode_plain(input),
+    }
+}
+
+/// Decodes a sequence starting with Escape after no more input came, as Alt with the key
+/// that follows it or as the Escape key itself.
+#[cfg(any(unix, test))]
+fn decode_lone_escape(input: &[u8]) -> (KeyEvent, usize) {
+    match input.get(1).and_then(|_| decode_plain(&input[1..])) {
+        Some((event, n)) => (event.with(Modifier::Alt), n + 1),
+        None => (KeyEvent::plain(Key::Escape), 1),
+    }
+}
+
+fn decode_plain(input: &[u8]) -> Option<(KeyEvent, usize)> {
+    let first = input[0];
+    let ctrl = |c: u8| KeyEvent::Key(Key::Char(c as char), Modifiers::new().with(Modifier::Ctrl));
+    let event = match first {
+        b'\r' | b'\n' => KeyEvent::plain(Key::Enter),
+        b'\t' => KeyEvent::plain(Key::Tab),
+        0x08 | 0x7f => KeyEvent::plain(Key::Backspace),
+        0x1b => KeyEvent::plain(Key::Escape),
+        0x00 => ctrl(b' '),
+        0x01..=0x1a => ctrl(first - 1 + b'a'),
+        0x1c..=0x1f => ctrl(first + 0x40),
+        _ => return decode_char(input),
+    };
+    Some((event, 1))
+}
+
+fn decode_char(input: &[u8]) -> Option<(KeyEvent, usize)> {
+    let len = match input[0] {
+        0x00..=0x7f => 1,
+        0xc2..=0xdf => 2,
+        0xe0..=0xef => 3,
+        0xf0..=0xf4 => 4,
+        _ => return Some((KeyEvent::Unknown(input[..1].to_vec()), 1)),
+    };
+    if input.len() < len {
+        return None;
+    }
+    match std::str::from_utf8(&input[..len]) {
+        Ok(s) => Some((KeyEvent::plain(Key::Char(s.chars().next()?)), len)),
+        Err(_) => Some((KeyEvent::Unknown(input[..1].to_vec()), 1)),
+    }
+}
+
+fn decode_escape(input: &[u8]) -> Option<(KeyEvent, usize)> {
+    match input.get(1)? {
+        b'[' => decode_csi(input),
+        b'O' => decode_ss3(input),
+        // The first Escape stands alone; the second starts the next event.
+        0x1b => Some((KeyEvent::plain(Key::Escape), 1)),
+        _ => {
+            let (event, n) = decode_plain(&input[1..])?;
+            Some((event.with(Modifier::Alt), n + 1))
+        }
+    }
+}
+
+/// Decodes `ESC O` followed by one letter, which some terminals send for the arrows,
+/// Home, End and F1 to F4.
+fn decode_ss3(input: &[u8]) -> Option<(KeyEvent, usize)> {
+    let &last = input.get(2)?;
+    let event = match letter_key(last) {
+        Some(key) => KeyEvent::plain(key),
+        None => KeyEvent::Unknown(input[..3].to_vec()),
+    };
+    Some((event, 3))
+}
+
+/// Returns the key an escape sequence ending in `letter` stands for.
+fn letter_key(letter: u8) -> Option<Key> {
+    Some(match letter {
+        b'A' => Key::Up,
+        b'B' => Key::Down,
+        b'C' => Key::Right,
+        b'D' => Key::Left,
+        b'H' => Key::Home,
+        b'F' => Key::End,
+        b'P'..=b'S' => Key::F(letter - b'P' + 1),
+        _ => return None,
+    })
+}
+
+/// Returns the key an escape sequence ending in `~` stands for, from its first parameter.
+fn tilde_key(code: u32) -> Option<Key> {
+    Some(match code {
+        1 | 7 => Key::Home,
+        2 => Key::Insert,
+        3 => Key::Delete,
+        4 | 8 => Key::End,
+        5 => Key::PageUp,
+        6 => Key::PageDown,
+        11..=15 => Key::F((code - 10) as u8),
+        17..=21 => Key::F((code - 11) as u8),
+        23 | 24 => Key::F((code - 12) as u8),
+        _ => return None,
+    })
+}
+
+/// Decodes a control sequence: `ESC [`, parameters separated by `;`, and a final byte.
+fn decode_csi(input: &[u8]) -> Option<(KeyEvent, usize)> {
+    let mut end = 2;
+    loop {
+        match *input.get(end)? {
+            0x30..=0x3f => end += 1,
+            0x40..=0x7e => break,
+            // Not a sequence we know how to end; give up on what came so far.
+            _ => return Some((KeyEvent::Unknown(input[..end].to_vec()), end)),
+        }
+    }
+    let used = end + 1;
+    let unknown = || Some((KeyEvent::Unknown(input[..used].to_vec()), used));
+    if input[..used] == *PASTE_START {
+        return decode_paste(input);
+    }
+    let mut params = Vec::new();
+    for param in input[2..end].split(|&b| b == b';') {
+        match std::str::from_utf8(param).ok()?.parse::<u32>() {
+            Ok(n) => params.push(n),
+            Err(_) if param.is_empty() => params.push(1),
+            Err(_) => return unknown(),
+        }
+    }
+    let modifiers = Modifiers::from_param(params.get(1).copied().unwrap_or(1));
+    let key = match input[end] {
+        b'~' => params.first().copied().and_then(tilde_key),
+        b'Z' => {
+            return Some((
+                KeyEvent::Key(Key::Tab, modifiers.with(Modifier::Shift)),
+                used,
+            ))
+        }
+        letter => letter_key(letter),
+    };
+    match key {
+        Some(key) => Some((KeyEvent::Key(key, modifiers), used)),
+        None => unknown(),
+    }
+}
+
+/// Decodes a bracketed paste, which is incomplete until the closing sequence arrives.
+fn decode_paste(input: &[u8]) -> Option<(KeyEvent, usize)> {
+    let body = &input[PASTE_START.len()..];
+    let end = body
+        .windows(PASTE_END.len())
+        .position(|window| window == PASTE_END)?;
+    let text = String::from_utf8_lossy(&body[..end])
+        .replace("\r\n", "\n")
+        .replace('\r', "\n");
+    Some((
+        KeyEvent::Paste(text),
+        PASTE_START.len() + end + PASTE_END.len(),
+    ))
+}
+
+#[cfg(unix)]
+pub use self::unix::{KeyReader, RawMode};
+
+#[cfg(unix)]
+mod unix {
+    use super::*;
+    use crate::term::{self, ModeGuard};
+    use crate::ReadError;
+    use std::io::{self, Write};
+    use std::os::unix::io::{AsRawFd, RawFd};
+
+    /// How long to wait after an Escape for the rest of a sequence, in milliseconds.
+    const ESCAPE_TIMEOUT_MS: libc::c_int = 50;
+
+    /// Keeps stdin in raw mode until it is dropped.
+    ///
+    /// In raw mode input is passed on a byte at a time without echo. Ctrl-C still
+    /// interrupts the program, and the terminal is restored on a panic or signal too.
+    /// # Examples:
+    /// ```no_run
+    /// use cli_utils::keys::RawMode;
+    /// let raw = RawMode::enable().unwrap();
+    /// // read key presses here
+    /// drop(raw);
+    /// ```
+    pub struct RawMode {
+        _guard: ModeGuard,
+    }
+
+    impl RawMode {
+        /// Switches stdin to raw mode.
+        /// # Errors:
+        /// Returns an error if stdin is not a terminal.
+        pub fn enable() -> io::Result<Self> {
+            Self::on(io::stdin().as_raw_fd())
+        }
+
+        pub(super) fn on(fd: RawFd) -> io::Result<Self> {
+            Ok(RawMode {
+                _guard: ModeGuard::raw(fd)?,
+            })
+        }
+    }
+
+    /// Reads key events from stdin, keeping it in raw mode while it is alive.
+    ///
+    /// Bracketed paste is turned on as well, so pasted text arrives as one
+    /// `KeyEvent::Paste` rather than as key presses.
+    /// # Examples:
+    /// ```no_run
+    /// use cli_utils::keys::{Key, KeyEvent, KeyReader};
+    /// let mut keys = KeyReader::new().unwrap();
+    /// loop {
+    ///     match keys.read_key().unwrap() {
+    ///         KeyEvent::Key(Key::Char('q'), _) => break,
+    ///         event => println!("{}\r", event),
+    ///     }
+    /// }
+    /// ```
+    pub struct KeyReader {
+        fd: RawFd,
+        pending: Vec<u8>,
+        paste: bool,
+        _raw: RawMode,
+    }
+
+    impl KeyReader {
+        /// Switches stdin to raw mode and turns on bracketed paste.
+        /// # Errors:
+        /// Returns an error if stdin is not a terminal.
+        pub fn new() -> io::Result<Self> {
+            let mut reader = Self::on(io::stdin().as_raw_fd())?;
+            let mut stdout = io::stdout();
+            stdout.write_all(b"\x1b[?2004h")?;
+            stdout.flush()?;
+            reader.paste = true;
+            Ok(reader)
+        }
+
+        pub(super) fn on(fd: RawFd) -> io::Result<Self> {
+            Ok(KeyReader {
+                fd,
+                pending: Vec::new(),
+                paste: false,
+                _raw: RawMode::on(fd)?,
+            })
+        }
+
+        /// Waits for the next key event.
+        /// # Errors:
+        /// Returns `ReadError::Eof` if the terminal is closed and `ReadError::Io` if reading
+        /// fails.
+        pub fn read_key(&mut self) -> Result<KeyEvent, ReadError> {
+            loop {
+                if let Some((event, n)) = decode_key(&self.pending) {
+                    self.pending.drain(..n);
+                    return Ok(event);
+                }
+                let escape =
+                    self.pending.first() == Some(&0x1b) && !self.pending.starts_with(PASTE_START);
+                if escape && !term::wait_readable(self.fd, ESCAPE_TIMEOUT_MS)? {
+                    let (event, n) = decode_lone_escape(&self.pending);
+                    self.pending.drain(..n);
+                    return Ok(event);
+                }
+                let mut buf = [0; 256];
+                let n = term::read_bytes(self.fd, &mut buf)?;
+                if n == 0 {
+                    return Err(ReadError::Eof);
+                }
+                self.pending.extend_from_slice(&buf[..n]);
+            }
+        }
+    }
+
+    impl Drop for KeyReader {
+        fn drop(&mut self) {
+            if self.paste {
+                let mut stdout = io::stdout();
+                let _ = stdout.write_all(b"\x1b[?2004l");
+                let _ = stdout.flush();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(key: Key, modifiers: &[Modifier]) -> KeyEvent {
+        let mods = modifiers
+            .iter()
+            .fold(Modifiers::new(), |mods, &m| mods.with(m));
+        KeyEvent::Key(key, mods)
+    }
+
+    fn decode_all(mut input: &[u8]) -> Vec<KeyEvent> {
+        let mut events = Vec::new();
+        while let Some((event, n)) = decode_key(input) {
+            events.push(event);
+            input = &input[n..];
+        }
+        assert!(input.is_empty(), "left over: {:?}", input);
+        events
+    }
+
+    #[test]
+    fn test_characters_and_control_keys() {
+        assert_eq!(
+            decode_all("aé€😀\r\t\x7f".as_bytes()),
+            vec![
+                key(Key::Char('a'), &[]),
+                key(Key::Char('é'), &[]),
+                key(Key::Char('€'), &[]),
+                key(Key::Char('😀'), &[]),
+                key(Key::Enter, &[]),
+                key(Key::Tab, &[]),
+                key(Key::Backspace, &[]),
+            ]
+        );
+        assert_eq!(
+            decode_all(b"\x01\x17\x00\x1f"),
+            vec![
+                key(Key::Char('a'), &[Modifier::Ctrl]),
+                key(Key::Char('w'), &[Modifier::Ctrl]),
+                key(Key::Char(' '), &[Modifier::Ctrl]),
+                key(Key::Char('_'), &[Modifier::Ctrl]),
+            ]
+        );
+        assert_eq!(
+            decode_key(b"\xff"),
+            Some((KeyEvent::Unknown(vec![0xff]), 1))
+        );
+    }
+
+    #[test]
+    fn test_navigation_and_function_keys() {
+        assert_eq!(
+            decode_all(
+                b"\x1b[A\x1bOB\x1b[C\x1b[D\x1b[H\x1bOF\x1b[1~\x1b[4~\x1b[5~\x1b[6~\x1b[2~\x1b[3~"
+            ),
+            vec![
+                key(Key::Up, &[]),
+                key(Key::Down, &[]),
+                key(Key::Right, &[]),
+                key(Key::Left, &[]),
+                key(Key::Home, &[]),
+                key(Key::End, &[]),
+                key(Key::Home, &[]),
+                key(Key::End, &[]),
+                key(Key::PageUp, &[]),
+                key(Key::PageDown, &[]),
+                key(Key::Insert, &[]),
+                key(Key::Delete, &[]),
+            ]
+        );
+        assert_eq!(
+            decode_all(b"\x1bOP\x1b[1;2S\x1b[15~\x1b[21~\x1b[24;5~"),
+            vec![
+                key(Key::F(1), &[]),
+                key(Key::F(4), &[Modifier::Shift]),
+                key(Key::F(5), &[]),
+                key(Key::F(10), &[]),
+                key(Key::F(12), &[Modifier::Ctrl]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_modifiers() {
+        assert_eq!(
+            decode_all(b"\x1b[1;5C\x1b[1;3A\x1b[1;8H\x1b[Z\x1bb\x1b\x02"),
+            vec![
+                key(Key::Right, &[Modifier::Ctrl]),
+                key(Key::Up, &[Modifier::Alt]),
+                key(Key::Home, &[Modifier::Shift, Modifier::Alt, Modifier::Ctrl]),
+                key(Key::Tab, &[Modifier::Shift]),
+                key(Key::Char('b'), &[Modifier::Alt]),
+                key(Key::Char('b'), &[Modifier::Alt, Modifier::Ctrl]),
+            ]
+        );
+        assert_eq!(
+            key(Key::Char('x'), &[Modifier::Alt, Modifier::Ctrl]).to_string(),
+            "Ctrl-Alt-x"
+        );
+    }
+
+    #[test]
+    fn test_bracketed_paste() {
+        assert_eq!(
+            decode_all(b"\x1b[200~one\r\ntwo\rthree\x1b[201~x"),
+            vec![
+                KeyEvent::Paste("one\ntwo\nthree".to_string()),
+                key(Key::Char('x'), &[]),
+            ]
+        );
+        assert_eq!(decode_key(b"\x1b[200~not yet"), None);
+    }
+
+    #[test]
+    fn test_incomplete_and_unknown_sequences() {
+        for input in [
+            &b""[..],
+            b"\x1b",
+            b"\x1b[",
+            b"\x1b[1;5",
+            b"\x1bO",
+            b"\xe2\x82",
+        ] {
+            assert_eq!(decode_key(input), None, "{:?}", input);
+        }
+        assert_eq!(
+            decode_key(b"\x1b[99~a"),
+            Some((KeyEvent::Unknown(b"\x1b[99~".to_vec()), 5))
+        );
+        assert_eq!(decode_key(b"\x1b\x1b[A"), Some((key(Key::Escape, &[]), 1)));
+        assert_eq!(decode_lone_escape(b"\x1b"), (key(Key::Escape, &[]), 1));
+        assert_eq!(
+            decode_lone_escape(b"\x1b["),
+            (key(Key::Char('['), &[Modifier::Alt]), 2)
+        );
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_key_reader_on_a_pty() {
+        use crate::term::{get_attrs, open_pty};
+        use std::io::Write;
+        use std::os::unix::io::AsRawFd;
+
+        let (mut master, slave, _serial) = open_pty();
+        let fd = slave.as_raw_fd();
+        let reading = std::thread::spawn(move || {
+            let mut reader = KeyReader::on(fd).unwrap();
+            let events: Vec<KeyEvent> = (0..3).map(|_| reader.read_key().unwrap()).collect();
+            drop(reader);
+            (events, slave)
+        });
+        // Raw mode flushes pending input when it is switched on, so type only afterwards.
+        while get_attrs(fd).unwrap().c_lflag & libc::ICANON != 0 {
+            std::thread::sleep(std::time::Duration::from_millis(5));
+        }
+        master.write_all(b"\x1b[Bq").unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        master.write_all(b"\x1b").unwrap();
+        let (events, slave) = reading.join().unwrap();
+        assert_eq!(
+            events,
+            vec![
+                key(Key::Down, &[]),
+                key(Key::Char('q'), &[]),
+                key(Key::Escape, &[])
+            ]
+        );
+        assert_ne!(
+            get_attrs(slave.as_raw_fd()).unwrap().c_lflag & libc::ICANON,
+            0
+        );
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_key_reader_inside_raw_mode() {
+        use crate::term::{get_attrs, open_pty};
+        use std::io::Write;
+        use std::os::unix::io::AsRawFd;
+        use std::sync::mpsc;
+        use std::time::Duration;
+
+        let (mut master, slave, _serial) = open_pty();
+        let fd = slave.as_raw_fd();
+        let canonical = move || get_attrs(fd).unwrap().c_lflag & libc::ICANON != 0;
+        let (done, finished) = mpsc::channel();
+        std::thread::spawn(move || {
+            let raw = RawMode::on(fd).unwrap();
+            let mut reader = KeyReader::on(fd).unwrap();
+            let event = reader.read_key().unwrap();
+            drop(reader);
+            let still_raw = !canonical();
+            drop(raw);
+            let _ = done.send((event, still_raw, slave));
+        });
+        // Each switch to raw mode flushes pending input, so type until the reader sees it.
+        let mut result = None;
+        for _ in 0..250 {
+            if !canonical() {
+                master.write_all(b"x").unwrap();
+            }
+            if let Ok(finished) = finished.recv_timeout(Duration::from_millis(20)) {
+                result = Some(finished);
+                break;
+            }
+        }
+        let (event, still_raw, _slave) = result.expect("a key reader inside raw mode hung");
+        assert_eq!(event, key(Key::Char('x'), &[]));
+        assert!(still_raw);
+        assert!(canonical());
+    }
+}
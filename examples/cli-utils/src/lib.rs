@@ -7,14 +7,32 @@
 //! ```
 //! # Panics:
 //! The `read_stdin` function will panic if it fails to read a line with a message "Failed to read input line".
+//! Use `try_read_line` to handle the error instead.
 
-use std::io::{BufRead, BufReader};
+use std::io::BufRead;
 
 pub mod config;
 pub mod colors;
+mod complete;
+mod editor;
+mod error;
+pub mod keys;
+mod menu;
+mod password;
+mod prompt;
+#[cfg(unix)]
+mod term;
+
+pub use complete::{Candidate, Completer, PathCompleter};
+pub use editor::{History, LineEditor};
+pub use error::ReadError;
+pub use menu::{multi_select, select};
+pub use password::{read_password, Secret};
+pub use prompt::{confirm, prompt, Confirm, Prompt, PromptError};
 
 
 /// This function reads a line from stdin and returns it as a String.
+/// It returns an empty String at the end of input.
 /// It will panic if it fails to read a line with a message "Failed to read input line".
 /// # Examples:
 /// ```
@@ -22,23 +40,113 @@ pub mod colors;
 /// let input = read_stdin();
 /// ```
 pub fn read_stdin() -> String {
-    let stdin = std::io::stdin();
-    let mut reader = BufReader::new(stdin.lock());
-    _read_stdin(&mut reader)
+    _read_stdin(&mut std::io::stdin().lock())
 }
 
 fn _read_stdin<R: BufRead>(reader: &mut R) -> String {
-    let mut line = String::new();
-    reader
-        .read_line(&mut line)
-        .expect("Failed to read input line");
-    line.trim().to_string()
+    match _try_read_line(reader) {
+        Ok(line) => line,
+        Err(ReadError::Eof) => String::new(),
+        Err(e) => panic!("Failed to read input line: {}", e),
+    }
+}
+
+/// This function reads a line from stdin and returns it trimmed, like `read_stdin`,
+/// but returns an error instead of panicking.
+/// # Examples:
+/// ```
+/// use cli_utils::{try_read_line, ReadError};
+/// match try_read_line() {
+///     Ok(line) => println!("read {:?}", line),
+///     Err(ReadError::Eof) => println!("no more input"),
+///     Err(e) => eprintln!("{}", e),
+/// }
+/// ```
+/// # Errors:
+/// Returns `ReadError::Eof` if stdin is at its end, `ReadError::Io` if reading fails and
+/// `ReadError::InvalidUtf8` if the line is not valid UTF-8. The line is consumed in the
+/// last case, so the next call reads the following one.
+pub fn try_read_line() -> Result<String, ReadError> {
+    _try_read_line(&mut std::io::stdin().lock())
+}
+
+fn _try_read_line<R: BufRead>(reader: &mut R) -> Result<String, ReadError> {
+    let mut bytes = Vec::new();
+    if reader.read_until(b'\n', &mut bytes)? == 0 {
+        return Err(ReadError::Eof);
+    }
+    let line = String::from_utf8(bytes)?;
+    Ok(line.trim().to_string())
+}
+
+/// This function reads a line from stdin and returns it trimmed, or `None` at the end of input.
+/// Unlike `read_stdin`, an empty line is `Some("")`, so interactive loops know when to stop.
+/// It will panic if it fails to read a line with a message "Failed to read input line".
+/// # Examples:
+/// ```
+/// use cli_utils::read_line;
+/// while let Some(line) = read_line() {
+///     println!("{}", line);
+/// }
+/// ```
+pub fn read_line() -> Option<String> {
+    _read_line(&mut std::io::stdin().lock())
+}
+
+fn _read_line<R: BufRead>(reader: &mut R) -> Option<String> {
+    match _try_read_line(reader) {
+        Ok(line) => Some(line),
+        Err(ReadError::Eof) => None,
+        Err(e) => panic!("Failed to read input line: {}", e),
+    }
+}
+
+/// This function returns an iterator over the trimmed lines of `reader`.
+/// Empty lines are yielded as empty Strings; the iterator ends at the end of input.
+/// # Examples:
+/// ```
+/// use cli_utils::trimmed_lines;
+/// use std::io::Cursor;
+/// let lines: Vec<String> = trimmed_lines(Cursor::new("  one \n\ntwo"))
+///     .collect::<Result<_, _>>()
+///     .unwrap();
+/// assert_eq!(lines, ["one", "", "two"]);
+/// ```
+pub fn trimmed_lines<R: BufRead>(reader: R) -> TrimmedLines<R> {
+    TrimmedLines { reader }
+}
+
+/// An iterator over the trimmed lines of a reader, created by `trimmed_lines`.
+///
+/// Each item is a `Result`; a line that is not valid UTF-8 is reported as
+/// `ReadError::InvalidUtf8` and skipped, so iteration can continue with the next one.
+#[derive(Debug)]
+pub struct TrimmedLines<R> {
+    reader: R,
+}
+
+impl<R> TrimmedLines<R> {
+    /// Returns the underlying reader, for reading the rest of the input another way.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+impl<R: BufRead> Iterator for TrimmedLines<R> {
+    type Item = Result<String, ReadError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match _try_read_line(&mut self.reader) {
+            Err(ReadError::Eof) => None,
+            result => Some(result),
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
-   use super::_read_stdin;
-   use std::io::Cursor;
+   use super::{_read_line, _read_stdin, _try_read_line, trimmed_lines, ReadError};
+   use std::io::{self, BufReader, Cursor, Read};
 
    #[test]
    fn test_read_input() {
@@ -58,4 +166,49 @@ mod tests {
        assert_eq!(output, expected_output);
    }
 
+   #[test]
+   fn test_read_line_distinguishes_empty_lines_from_eof() {
+       let mut reader = Cursor::new("\n  \nlast");
+       assert_eq!(_read_line(&mut reader), Some(String::new()));
+       assert_eq!(_read_line(&mut reader), Some(String::new()));
+       assert_eq!(_read_line(&mut reader), Some("last".to_string()));
+       assert_eq!(_read_line(&mut reader), None);
+   }
+
+   #[test]
+   fn test_trimmed_lines_continue_after_invalid_utf8() {
+       let mut lines = trimmed_lines(Cursor::new(&b"a\n\xff\n\n b \n"[..]));
+       assert_eq!(lines.next().unwrap().unwrap(), "a");
+       assert!(matches!(lines.next(), Some(Err(ReadError::InvalidUtf8(_)))));
+       assert_eq!(lines.next().unwrap().unwrap(), "");
+       assert_eq!(lines.next().unwrap().unwrap(), "b");
+       assert!(lines.next().is_none());
+   }
+
+   #[test]
+   fn test_try_read_line_distinguishes_errors() {
+       let mut reader = Cursor::new(&b"caf\xe9\n  next \n"[..]);
+       match _try_read_line(&mut reader) {
+           Err(ReadError::InvalidUtf8(e)) => assert_eq!(e.into_bytes(), b"caf\xe9\n"),
+           other => panic!("expected invalid UTF-8, got {:?}", other),
+       }
+       assert_eq!(_try_read_line(&mut reader).unwrap(), "next");
+       assert!(_try_read_line(&mut reader).unwrap_err().is_eof());
+   }
+
+   #[test]
+   fn test_try_read_line_reports_io_errors() {
+       struct BrokenPipe;
+       impl Read for BrokenPipe {
+           fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+               Err(io::Error::from(io::ErrorKind::BrokenPipe))
+           }
+       }
+       let mut reader = BufReader::new(BrokenPipe);
+       match _try_read_line(&mut reader) {
+           Err(ReadError::Io(e)) => assert_eq!(e.kind(), io::ErrorKind::BrokenPipe),
+           other => panic!("expected an I/O error, got {:?}", other),
+       }
+   }
+
 }
@@ -0,0 +1,346 @@
+//! Picking one or several items from a list.
+
+#[cfg(any(unix, test))]
+use crate::colors::{color_depth, truncate_to_width, Role, Theme};
+#[cfg(unix)]
+use crate::colors::{colors_enabled, Stream};
+#[cfg(unix)]
+use crate::keys::{Key, KeyEvent};
+use crate::{_try_read_line, PromptError};
+use std::fmt;
+#[cfg(unix)]
+use std::io::IsTerminal;
+use std::io::{self, BufRead, Write};
+
+/// A key that moves around a menu.
+#[cfg(any(unix, test))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MenuKey {
+    Up,
+    Down,
+    Toggle,
+    Confirm,
+}
+
+/// Returns the menu key a key press stands for: the arrow keys or `k` and `j`, space and
+/// enter.
+#[cfg(unix)]
+fn menu_key(event: &KeyEvent) -> Option<MenuKey> {
+    let KeyEvent::Key(key, modifiers) = event else {
+        return None;
+    };
+    if !modifiers.is_empty() {
+        return None;
+    }
+    match key {
+        Key::Up | Key::Char('k') => Some(MenuKey::Up),
+        Key::Down | Key::Char('j') => Some(MenuKey::Down),
+        Key::Char(' ') => Some(MenuKey::Toggle),
+        Key::Enter => Some(MenuKey::Confirm),
+        _ => None,
+    }
+}
+
+/// The cursor and, for a multi-select menu, the checked items.
+struct Menu<'a, T> {
+    prompt: &'a str,
+    items: &'a [T],
+    /// The item key presses move around; the numbered fallback has none.
+    #[cfg(any(unix, test))]
+    cursor: usize,
+    checked: Option<Vec<bool>>,
+}
+
+impl<'a, T: fmt::Display> Menu<'a, T> {
+    fn new(prompt: &'a str, items: &'a [T], multi: bool) -> Self {
+        Self {
+            prompt,
+            items,
+            #[cfg(any(unix, test))]
+            cursor: 0,
+            checked: multi.then(|| vec![false; items.len()]),
+        }
+    }
+
+    /// Applies `key`, returning the chosen indices once the menu is confirmed.
+    #[cfg(any(unix, test))]
+    fn handle(&mut self, key: MenuKey) -> Option<Vec<usize>> {
+        let last = self.items.len() - 1;
+        match key {
+            MenuKey::Up => {
+                self.cursor = if self.cursor == 0 {
+                    last
+                } else {
+                    self.cursor - 1
+                }
+            }
+            MenuKey::Down => {
+                self.cursor = if self.cursor == last {
+                    0
+                } else {
+                    self.cursor + 1
+                }
+            }
+            MenuKey::Toggle => {
+                if let Some(checked) = &mut self.checked {
+                    checked[self.cursor] = !checked[self.cursor];
+                }
+            }
+            MenuKey::Confirm => {
+                return Some(match &self.checked {
+                    Some(checked) => (0..checked.len()).filter(|&i| checked[i]).collect(),
+                    None => vec![self.cursor],
+                })
+            }
+        }
+        None
+    }
+
+    /// Draws the prompt and one line per item, the current one marked with `>`. Lines are
+    /// cut to `width` columns, so each one takes a single row of the terminal.
+    #[cfg(any(unix, test))]
+    fn render(&self, colored: bool, width: usize) -> String {
+        let theme = Theme::dark();
+        let paint = |role: Role, s: &str| {
+            if colored {
+                theme.style(role).paint_for(s, color_depth())
+            } else {
+                s.to_string()
+            }
+        };
+        let clip = |line: String| format!("{}\n", truncate_to_width(&line, width));
+        let mut out = clip(paint(Role::Emphasis, self.prompt));
+        for (i, item) in self.items.iter().enumerate() {
+            let mut line = String::new();
+            if let Some(checked) = &self.checked {
+                line.push_str(if checked[i] { "[x] " } else { "[ ] " });
+            }
+            line.push_str(&item.to_string());
+            if i == self.cursor {
+                out.push_str(&clip(format!("> {}", paint(Role::Info, &line))));
+            } else {
+                out.push_str(&clip(format!("  {}", line)));
+            }
+        }
+        out
+    }
+}
+
+/// Lets the user pick one of `items` and returns its index.
+///
+/// On a terminal the items are drawn as a list: the arrow keys or `j` and `k` move, and
+/// enter picks the current item. Otherwise the items are numbered and a number is read
+/// from stdin, asking again until it is one of them.
+/// # Examples:
+/// ```no_run
+/// use cli_utils::select;
+/// let environments = ["staging", "production"];
+/// let picked = select("Deploy to", &environments).unwrap();
+/// println!("deploying to {}", environments[picked]);
+/// ```
+/// # Errors:
+/// Returns `PromptError::NoChoices` if `items` is empty, `PromptError::Read` if stdin
+/// ends or cannot be read, and `PromptError::Write` if stdout cannot be written.
+pub fn select<T: fmt::Display>(prompt: &str, items: &[T]) -> Result<usize, PromptError> {
+    run(Menu::new(prompt, items, false)).map(|chosen| chosen[0])
+}
+
+/// Lets the user pick any number of `items` and returns their indices in ascending order.
+///
+/// On a terminal space toggles the current item and enter confirms. Otherwise the items
+/// are numbered and numbers separated by spaces or commas are read from stdin; an empty
+/// answer picks nothing.
+/// # Examples:
+/// ```no_run
+/// use cli_utils::multi_select;
+/// let services = ["api", "worker", "scheduler"];
+/// for i in multi_select("Restart", &services).unwrap() {
+///     println!("restarting {}", services[i]);
+/// }
+/// ```
+/// # Errors:
+/// See `select`.
+pub fn multi_select<T: fmt::Display>(prompt: &str, items: &[T]) -> Result<Vec<usize>, PromptError> {
+    run(Menu::new(prompt, items, true))
+}
+
+fn run<T: fmt::Display>(menu: Menu<'_, T>) -> Result<Vec<usize>, PromptError> {
+    if menu.items.is_empty() {
+        return Err(PromptError::NoChoices);
+    }
+    let stdin = io::stdin();
+    #[cfg(unix)]
+    if stdin.is_terminal() && io::stdout().is_terminal() {
+        return run_interactive(menu);
+    }
+    run_numbered(menu, &mut stdin.lock(), &mut io::stdout())
+}
+
+#[cfg(unix)]
+fn run_interactive<T: fmt::Display>(mut menu: Menu<'_, T>) -> Result<Vec<usize>, PromptError> {
+    let colored = colors_enabled(Stream::Stdout);
+    let mut stdout = io::stdout();
+    let write = |stdout: &mut io::Stdout, s: &str| {
+        stdout
+            .write_all(s.as_bytes())
+            .and_then(|_| stdout.flush())
+            .map_err(PromptError::Write)
+    };
+    let lines = menu.items.len() + 1;
+    let width = || crate::term::columns(std::os::unix::io::AsRawFd::as_raw_fd(&io::stdout()));
+    let mut keys = crate::keys::KeyReader::new().map_err(|e| PromptError::Read(e.into()))?;
+    let cursor = crate::term::HiddenCursor::new().map_err(PromptError::Write)?;
+    let result: Result<Vec<usize>, PromptError> = (|| {
+        write(&mut stdout, &menu.render(colored, width().unwrap_or(80)))?;
+        loop {
+            let Some(key) = menu_key(&keys.read_key().map_err(PromptError::Read)?) else {
+                continue;
+            };
+            let chosen = menu.handle(key);
+            // Move back to the prompt and clear everything below it.
+            write(&mut stdout, &format!("\x1b[{}A\r\x1b[J", lines))?;
+            match chosen {
+                Some(chosen) => return Ok(chosen),
+                None => write(&mut stdout, &menu.render(colored, width().unwrap_or(80)))?,
+            }
+        }
+    })();
+    drop(cursor);
+    let chosen = result?;
+    let summary: Vec<String> = chosen.iter().map(|&i| menu.items[i].to_string()).collect();
+    write(
+        &mut stdout,
+        &format!("{} {}\n", menu.prompt, summary.join(", ")),
+    )?;
+    Ok(chosen)
+}
+
+fn run_numbered<T: fmt::Display, R: BufRead, W: Write>(
+    menu: Menu<'_, T>,
+    reader: &mut R,
+    writer: &mut W,
+) -> Result<Vec<usize>, PromptError> {
+    let count = menu.items.len();
+    let mut list = format!("{}\n", menu.prompt);
+    for (i, item) in menu.items.iter().enumerate() {
+        list.push_str(&format!("{:>3}) {}\n", i + 1, item));
+    }
+    writer
+        .write_all(list.as_bytes())
+        .map_err(PromptError::Write)?;
+    let multi = menu.checked.is_some();
+    loop {
+        let question = if multi {
+            format!(
+                "Enter numbers from 1 to {}, separated by spaces or commas: ",
+                count
+            )
+        } else {
+            format!("Enter a number from 1 to {}: ", count)
+        };
+        write!(writer, "{}", question)
+            .and_then(|_| writer.flush())
+            .map_err(PromptError::Write)?;
+        let line = _try_read_line(reader)?;
+        let numbers: Result<Vec<usize>, _> = line
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|part| !part.is_empty())
+            .map(|part| part.parse::<usize>())
+            .collect();
+        let error = match numbers {
+            Ok(numbers) if numbers.iter().any(|&n| n == 0 || n > count) => {
+                format!("{:?} is not a number from 1 to {}", line, count)
+            }
+            Ok(numbers) if !multi && numbers.len() != 1 => "enter exactly one number".to_string(),
+            Ok(mut numbers) => {
+                numbers.sort_unstable();
+                numbers.dedup();
+                return Ok(numbers.into_iter().map(|n| n - 1).collect());
+            }
+            Err(_) => format!("{:?} is not a number from 1 to {}", line, count),
+        };
+        writeln!(writer, "{}", error).map_err(PromptError::Write)?;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ReadError;
+    use std::io::Cursor;
+
+    fn numbered(multi: bool, input: &str) -> (Result<Vec<usize>, PromptError>, String) {
+        let items = ["staging", "production", "dev"];
+        let mut output = Vec::new();
+        let menu = Menu::new("Pick", &items, multi);
+        let result = run_numbered(menu, &mut Cursor::new(input), &mut output);
+        (result, String::from_utf8(output).unwrap())
+    }
+
+    #[test]
+    fn test_keys_move_toggle_and_confirm() {
+        let items = ["a", "b", "c"];
+        let mut menu = Menu::new("Pick", &items, true);
+        assert_eq!(menu.handle(MenuKey::Up), None);
+        assert_eq!(menu.cursor, 2);
+        menu.handle(MenuKey::Toggle);
+        menu.handle(MenuKey::Down);
+        menu.handle(MenuKey::Toggle);
+        assert_eq!(menu.render(false, 80), "Pick\n> [x] a\n  [ ] b\n  [x] c\n");
+        assert_eq!(menu.handle(MenuKey::Confirm), Some(vec![0, 2]));
+
+        let mut menu = Menu::new("Pick", &items, false);
+        menu.handle(MenuKey::Down);
+        menu.handle(MenuKey::Toggle);
+        assert_eq!(menu.handle(MenuKey::Confirm), Some(vec![1]));
+    }
+
+    #[test]
+    fn test_render_cuts_long_items_to_the_width() {
+        let items = ["production-eu-west-1", "dev"];
+        let menu = Menu::new("Deploy to which one", &items, true);
+        assert_eq!(
+            menu.render(false, 12),
+            "Deploy to wh\n> [ ] produc\n  [ ] dev\n"
+        );
+        let colored = menu.render(true, 12);
+        assert!(colored
+            .lines()
+            .all(|line| crate::colors::display_width(line) <= 12));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_menu_keys() {
+        let key = |bytes: &[u8]| menu_key(&crate::keys::decode_key(bytes).unwrap().0);
+        assert_eq!(key(b"\x1b[A"), Some(MenuKey::Up));
+        assert_eq!(key(b"\x1bOA"), Some(MenuKey::Up));
+        assert_eq!(key(b"j"), Some(MenuKey::Down));
+        assert_eq!(key(b"\r"), Some(MenuKey::Confirm));
+        assert_eq!(key(b"\x1bj"), None);
+        assert_eq!(key(b"x"), None);
+    }
+
+    #[test]
+    fn test_numbered_fallback_asks_again() {
+        let (result, output) = numbered(false, "4\n1 2\n2\n");
+        assert_eq!(result.unwrap(), vec![1]);
+        assert_eq!(
+            output,
+            "Pick\n  1) staging\n  2) production\n  3) dev\n\
+             Enter a number from 1 to 3: \"4\" is not a number from 1 to 3\n\
+             Enter a number from 1 to 3: enter exactly one number\n\
+             Enter a number from 1 to 3: "
+        );
+    }
+
+    #[test]
+    fn test_numbered_fallback_for_several_items() {
+        assert_eq!(numbered(true, "3, 1 3\n").0.unwrap(), vec![0, 2]);
+        assert_eq!(numbered(true, "\n").0.unwrap(), Vec::<usize>::new());
+        assert!(matches!(
+            numbered(true, "").0,
+            Err(PromptError::Read(ReadError::Eof))
+        ));
+    }
+}
@@ -0,0 +1,209 @@
+//! Reading passwords and other secrets without echoing them.
+
+use crate::ReadError;
+use std::fmt;
+#[cfg(unix)]
+use std::io::IsTerminal;
+use std::io::{self, Read, Write};
+use zeroize::{Zeroize, Zeroizing};
+
+/// A string that is overwritten with zeros when dropped, such as a password.
+///
+/// It implements neither `Display` nor `Clone`, and its `Debug` output hides the contents,
+/// so it is not copied or logged by accident.
+/// # Examples:
+/// ```
+/// use cli_utils::Secret;
+/// let secret = Secret::from("hunter2".to_string());
+/// assert_eq!(secret.expose(), "hunter2");
+/// assert_eq!(format!("{:?}", secret), "Secret(\"***\")");
+/// ```
+pub struct Secret(String);
+
+impl Secret {
+    /// Returns the secret itself. Avoid keeping copies of it around.
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl From<String> for Secret {
+    fn from(s: String) -> Self {
+        Secret(s)
+    }
+}
+
+impl PartialEq for Secret {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl Eq for Secret {}
+
+impl fmt::Debug for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Secret").field(&"***").finish()
+    }
+}
+
+impl Drop for Secret {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+/// Writes `prompt` to stderr and reads a line from stdin without echoing it.
+///
+/// Echo is turned back on when the line has been read, when reading fails, on a panic,
+/// and on Ctrl-C. A terminal is read directly rather than through stdin's buffer, so the
+/// password is only ever held in memory that is zeroed. When stdin is not a terminal, a
+/// line is read as it is, so passwords can be piped in. Unlike `read_stdin`, only the line
+/// ending is removed: spaces around the password are kept.
+/// # Examples:
+/// ```no_run
+/// use cli_utils::read_password;
+/// let password = read_password("Password: ").unwrap();
+/// assert!(!password.is_empty());
+/// ```
+/// # Errors:
+/// Returns `ReadError::Eof` if stdin is at its end and `ReadError::Io` if reading fails,
+/// the terminal settings cannot be changed, or the password is not valid UTF-8.
+pub fn read_password(prompt: &str) -> Result<Secret, ReadError> {
+    let stdin = io::stdin();
+    let mut stderr = io::stderr();
+    #[cfg(unix)]
+    if stdin.is_terminal() {
+        use std::os::unix::io::AsRawFd;
+        return _read_password(stdin.as_raw_fd(), &mut stderr, prompt);
+    }
+    write_prompt(&mut stderr, prompt)?;
+    read_secret_line(&mut stdin.lock())
+}
+
+#[cfg(unix)]
+fn _read_password<W: Write>(
+    tty: std::os::unix::io::RawFd,
+    writer: &mut W,
+    prompt: &str,
+) -> Result<Secret, ReadError> {
+    let _guard = crate::term::ModeGuard::no_echo(tty)?;
+    write_prompt(writer, prompt)?;
+    read_secret_line(&mut Tty(tty))
+}
+
+/// Reads the terminal with `read(2)`, without a buffer that would keep a copy of the input.
+#[cfg(unix)]
+struct Tty(std::os::unix::io::RawFd);
+
+#[cfg(unix)]
+impl Read for Tty {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        crate::term::read_bytes(self.0, buf)
+    }
+}
+
+fn write_prompt<W: Write>(writer: &mut W, prompt: &str) -> io::Result<()> {
+    writer.write_all(prompt.as_bytes())?;
+    writer.flush()
+}
+
+/// Reads one line without its line ending, a byte at a time, into buffers that are
+/// zeroed when dropped. Nothing after the line is consumed.
+fn read_secret_line<R: Read>(reader: &mut R) -> Result<Secret, ReadError> {
+    let mut bytes = Zeroizing::new(Vec::with_capacity(64));
+    let mut byte = Zeroizing::new([0]);
+    loop {
+        match reader.read(&mut byte[..]) {
+            Ok(0) if bytes.is_empty() => return Err(ReadError::Eof),
+            Ok(0) => break,
+            Ok(_) if byte[0] == b'\n' => break,
+            Ok(_) => {
+                if bytes.len() == bytes.capacity() {
+                    // Growing in place would free the old buffer without zeroing it.
+                    let mut grown = Zeroizing::new(Vec::with_capacity(bytes.capacity() * 2));
+                    grown.extend_from_slice(&bytes);
+                    bytes = grown;
+                }
+                bytes.push(byte[0]);
+            }
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(ReadError::Io(e)),
+        }
+    }
+    while bytes.last() == Some(&b'\r') {
+        bytes.pop();
+    }
+    match std::str::from_utf8(&bytes) {
+        Ok(line) => Ok(Secret(line.to_string())),
+        // The error is built without the bytes, which would carry the secret with it.
+        Err(_) => Err(ReadError::Io(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "password is not valid UTF-8",
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_read_secret_line_keeps_spaces() {
+        let mut reader = Cursor::new(" pass word \r\nnext\n");
+        assert_eq!(
+            read_secret_line(&mut reader).unwrap().expose(),
+            " pass word "
+        );
+        assert_eq!(read_secret_line(&mut reader).unwrap().expose(), "next");
+        let long = "x".repeat(200);
+        let mut reader = Cursor::new(format!("{}\nrest", long));
+        assert_eq!(read_secret_line(&mut reader).unwrap().expose(), long);
+        assert_eq!(read_secret_line(&mut reader).unwrap().expose(), "rest");
+        assert!(read_secret_line(&mut reader).unwrap_err().is_eof());
+        let error = read_secret_line(&mut Cursor::new(&b"\xff\n"[..])).unwrap_err();
+        assert!(!error.to_string().contains('\u{fffd}'));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_read_password_hides_input_on_a_pty() {
+        use crate::term::{get_attrs, open_pty};
+        use std::os::unix::io::AsRawFd;
+
+        let (mut master, slave, _serial) = open_pty();
+        let fd = slave.as_raw_fd();
+        let reading = std::thread::spawn(move || {
+            let mut prompt = Vec::new();
+            let secret = _read_password(fd, &mut prompt, "Password: ");
+            (secret, prompt, slave)
+        });
+        // Echo happens as input arrives, so type only once it is off.
+        while get_attrs(fd).unwrap().c_lflag & libc::ECHO != 0 {
+            std::thread::sleep(std::time::Duration::from_millis(5));
+        }
+        master.write_all(b"hunter2\n").unwrap();
+        let (secret, prompt, slave) = reading.join().unwrap();
+        assert_eq!(secret.unwrap().expose(), "hunter2");
+        assert_eq!(prompt, b"Password: ");
+        assert_ne!(
+            get_attrs(slave.as_raw_fd()).unwrap().c_lflag & libc::ECHO,
+            0
+        );
+
+        // Only the newline was echoed back to the terminal.
+        unsafe { libc::fcntl(master.as_raw_fd(), libc::F_SETFL, libc::O_NONBLOCK) };
+        let mut echoed = Vec::new();
+        let _ = master.read_to_end(&mut echoed);
+        assert!(!String::from_utf8_lossy(&echoed).contains("hunter2"));
+    }
+}
@@ -0,0 +1,491 @@
+//! Asking a question on the terminal and parsing the answer.
+
+use crate::colors::{color_depth, colors_enabled, ColorChoice, Stream, Style};
+use crate::{_try_read_line, Completer, ReadError};
+use std::fmt;
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::str::FromStr;
+
+type Validator<'a, T> = Box<dyn Fn(&T) -> Result<(), String> + 'a>;
+
+/// A question whose answer is parsed into a `T`, asked again until the answer is valid.
+/// # Examples:
+/// ```
+/// use cli_utils::Prompt;
+/// use std::io::Cursor;
+/// let mut input = Cursor::new("eighty\n80\n\n");
+/// let mut output = Vec::new();
+/// let port: u16 = Prompt::new("Port")
+///     .default(8080)
+///     .validate(|port: &u16| if *port < 1024 { Err("pick a port above 1023".to_string()) } else { Ok(()) })
+///     .retries(2)
+///     .read_from(&mut input, &mut output)
+///     .unwrap();
+/// assert_eq!(port, 8080);
+/// ```
+pub struct Prompt<'a, T> {
+    message: String,
+    default: Option<(T, String)>,
+    validator: Option<Validator<'a, T>>,
+    retries: Option<usize>,
+    completer: Option<Box<dyn Completer>>,
+}
+
+impl<'a, T> Prompt<'a, T>
+where
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    /// Creates a prompt that shows `message` and asks again for as long as the answer is invalid.
+    pub fn new(message: &str) -> Self {
+        Self {
+            message: message.to_string(),
+            default: None,
+            validator: None,
+            retries: None,
+            completer: None,
+        }
+    }
+
+    /// Returns `value` when the answer is empty or the input has ended. The value is shown
+    /// in brackets after the message and is not validated.
+    pub fn default(mut self, value: T) -> Self
+    where
+        T: fmt::Display,
+    {
+        let text = value.to_string();
+        self.default = Some((value, text));
+        self
+    }
+
+    /// Rejects parsed answers for which `validator` returns an error message.
+    pub fn validate(mut self, validator: impl Fn(&T) -> Result<(), String> + 'a) -> Self {
+        self.validator = Some(Box::new(validator));
+        self
+    }
+
+    /// Gives up after `retries` invalid answers following the first one.
+    pub fn retries(mut self, retries: usize) -> Self {
+        self.retries = Some(retries);
+        self
+    }
+
+    /// Completes the answer with `completer` when Tab is pressed on a terminal.
+    /// # Examples:
+    /// ```no_run
+    /// use cli_utils::{PathCompleter, Prompt};
+    /// let config: String = Prompt::new("Config file")
+    ///     .completer(PathCompleter::new())
+    ///     .read()
+    ///     .unwrap();
+    /// ```
+    pub fn completer(mut self, completer: impl Completer + 'static) -> Self {
+        self.completer = Some(Box::new(completer));
+        self
+    }
+
+    /// Asks on stdout and reads the answer from stdin. With a completer, answers are read
+    /// with a `LineEditor` when both are terminals.
+    /// # Errors:
+    /// See `read_from`.
+    pub fn read(self) -> Result<T, PromptError> {
+        let stdin = io::stdin();
+        #[cfg(unix)]
+        if self.completer.is_some() && stdin.is_terminal() && io::stdout().is_terminal() {
+            let mut prompt = self;
+            let completer = prompt.completer.take();
+            // Answers are not kept, so there is no history to recall them from.
+            let history = crate::History::new(0);
+            return prompt.ask(&mut io::stdout(), |_, question| {
+                crate::editor::edit_line(question, &history, completer.as_deref())
+                    .map(|line| line.trim().to_string())
+                    .map_err(PromptError::Read)
+            });
+        }
+        self.read_from(&mut stdin.lock(), &mut io::stdout())
+    }
+
+    /// Writes the question to `writer` and reads answers from `reader` until one parses
+    /// and passes the validator. Each invalid answer is followed by a line explaining why.
+    /// # Errors:
+    /// Returns `PromptError::Read` if the input ends without a default or cannot be read,
+    /// `PromptError::Write` if the question cannot be written, and
+    /// `PromptError::TooManyAttempts` once the retries are used up.
+    pub fn read_from<R: BufRead, W: Write>(
+        self,
+        reader: &mut R,
+        writer: &mut W,
+    ) -> Result<T, PromptError> {
+        self.ask(writer, |writer, question| {
+            write!(writer, "{}", question)
+                .and_then(|_| writer.flush())
+                .map_err(PromptError::Write)?;
+            _try_read_line(reader).map_err(PromptError::Read)
+        })
+    }
+
+    /// Asks until an answer is valid, getting each answer to the question from
+    /// `read_answer` and writing the reasons for rejecting them to `writer`.
+    fn ask<W: Write>(
+        self,
+        writer: &mut W,
+        mut read_answer: impl FnMut(&mut W, &str) -> Result<String, PromptError>,
+    ) -> Result<T, PromptError> {
+        let mut attempts = 0;
+        loop {
+            let question = match &self.default {
+                Some((_, text)) => format!("{} [{}]: ", self.message, text),
+                None => format!("{}: ", self.message),
+            };
+            let line = match read_answer(writer, &question) {
+                Ok(line) => line,
+                Err(PromptError::Read(ReadError::Eof)) if self.default.is_some() => String::new(),
+                Err(e) => return Err(e),
+            };
+            if line.is_empty() {
+                if let Some((value, _)) = self.default {
+                    return Ok(value);
+                }
+            }
+            let error = match line.parse::<T>() {
+                Ok(value) => match self.validator.as_ref().map_or(Ok(()), |v| v(&value)) {
+                    Ok(()) => return Ok(value),
+                    Err(message) => message,
+                },
+                Err(_) if line.is_empty() => "an answer is required".to_string(),
+                Err(e) => format!("invalid value {:?}: {}", line, e),
+            };
+            writeln!(writer, "{}", error).map_err(PromptError::Write)?;
+            attempts += 1;
+            if self.retries.is_some_and(|retries| attempts > retries) {
+                return Err(PromptError::TooManyAttempts {
+                    attempts,
+                    last_error: error,
+                });
+            }
+        }
+    }
+}
+
+impl<T> fmt::Debug for Prompt<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Prompt")
+            .field("message", &self.message)
+            .field("default", &self.default.as_ref().map(|(_, text)| text))
+            .field("retries", &self.retries)
+            .field("completer", &self.completer.is_some())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Asks `message` on stdout and parses the answer read from stdin, asking again until it parses.
+/// # Examples:
+/// ```no_run
+/// use cli_utils::prompt;
+/// let age: u8 = prompt("Age").unwrap();
+/// ```
+/// # Errors:
+/// Returns `PromptError::Read` if stdin ends or cannot be read, and `PromptError::Write`
+/// if stdout cannot be written.
+pub fn prompt<T>(message: &str) -> Result<T, PromptError>
+where
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    Prompt::new(message).read()
+}
+
+/// A yes/no question. Answers are `y`, `yes`, `n` or `no` in any case; an empty answer
+/// picks the default, which is highlighted in the `[Y/n]` hint.
+/// # Examples:
+/// ```
+/// use cli_utils::Confirm;
+/// use std::io::Cursor;
+/// let mut output = Vec::new();
+/// let delete = Confirm::new("Delete 12 files?", false)
+///     .read_from(&mut Cursor::new("maybe\nYES\n"), &mut output)
+///     .unwrap();
+/// assert!(delete);
+/// ```
+///
+/// Skipping the question when a `--yes` flag was given:
+/// ```
+/// use cli_utils::Confirm;
+/// let yes = std::env::args().any(|arg| arg == "--yes");
+/// let delete = Confirm::new("Delete 12 files?", false).assume(yes.then_some(true)).read();
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Confirm {
+    message: String,
+    default: bool,
+    assumed: Option<bool>,
+    color: ColorChoice,
+}
+
+impl Confirm {
+    /// Creates a question answered with `default` when the answer is empty, the input has
+    /// ended or stdin is not a terminal.
+    pub fn new(message: &str, default: bool) -> Self {
+        Self {
+            message: message.to_string(),
+            default,
+            assumed: None,
+            color: ColorChoice::Auto,
+        }
+    }
+
+    /// Answers with `answer`, if there is one, without asking.
+    pub fn assume(mut self, answer: Option<bool>) -> Self {
+        self.assumed = answer;
+        self
+    }
+
+    /// Sets whether the default choice is highlighted. `Auto` highlights it when colors
+    /// are enabled for stdout.
+    pub fn color(mut self, color: ColorChoice) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Asks on stdout and reads the answer from stdin. Returns the assumed answer or,
+    /// when stdin is not a terminal, the default without reading anything.
+    /// # Errors:
+    /// See `read_from`.
+    pub fn read(self) -> Result<bool, PromptError> {
+        if let Some(answer) = self.assumed {
+            return Ok(answer);
+        }
+        let stdin = io::stdin();
+        if !stdin.is_terminal() {
+            return Ok(self.default);
+        }
+        self.read_from(&mut stdin.lock(), &mut io::stdout())
+    }
+
+    /// Writes the question to `writer` and reads answers from `reader` until one is
+    /// yes or no, returning the assumed answer without asking if there is one.
+    /// # Errors:
+    /// Returns `PromptError::Read` if the input cannot be read and `PromptError::Write`
+    /// if the question cannot be written.
+    pub fn read_from<R: BufRead, W: Write>(
+        self,
+        reader: &mut R,
+        writer: &mut W,
+    ) -> Result<bool, PromptError> {
+        if let Some(answer) = self.assumed {
+            return Ok(answer);
+        }
+        let colored = match self.color {
+            ColorChoice::Auto => colors_enabled(Stream::Stdout),
+            choice => choice == ColorChoice::Always,
+        };
+        let highlight = |choice: &str| {
+            if colored {
+                Style::new()
+                    .bold()
+                    .underline()
+                    .paint_for(choice, color_depth())
+            } else {
+                choice.to_string()
+            }
+        };
+        let hint = if self.default {
+            format!("[{}/n]", highlight("Y"))
+        } else {
+            format!("[y/{}]", highlight("N"))
+        };
+        loop {
+            write!(writer, "{} {} ", self.message, hint)
+                .and_then(|_| writer.flush())
+                .map_err(PromptError::Write)?;
+            let line = match _try_read_line(reader) {
+                Ok(line) => line,
+                Err(ReadError::Eof) => return Ok(self.default),
+                Err(e) => return Err(PromptError::Read(e)),
+            };
+            match line.to_ascii_lowercase().as_str() {
+                "" => return Ok(self.default),
+                "y" | "yes" => return Ok(true),
+                "n" | "no" => return Ok(false),
+                _ => writeln!(writer, "please answer y or n").map_err(PromptError::Write)?,
+            }
+        }
+    }
+}
+
+/// Asks a yes/no question on stdout and reads the answer from stdin, see `Confirm`.
+/// # Examples:
+/// ```
+/// use cli_utils::confirm;
+/// if confirm("Delete 12 files?", false).unwrap() {
+///     println!("deleting");
+/// }
+/// ```
+/// # Errors:
+/// Returns `PromptError::Read` if stdin cannot be read and `PromptError::Write` if stdout
+/// cannot be written.
+pub fn confirm(message: &str, default: bool) -> Result<bool, PromptError> {
+    Confirm::new(message, default).read()
+}
+
+/// The error returned when a prompt gets no valid answer.
+#[derive(Debug)]
+pub enum PromptError {
+    Read(ReadError),
+    Write(io::Error),
+    /// Every allowed answer was invalid; `last_error` explains the last one.
+    TooManyAttempts {
+        attempts: usize,
+        last_error: String,
+    },
+    /// A menu was given no items to choose from.
+    NoChoices,
+}
+
+impl fmt::Display for PromptError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PromptError::Read(e) => e.fmt(f),
+            PromptError::Write(e) => write!(f, "failed to write prompt: {}", e),
+            PromptError::TooManyAttempts {
+                attempts,
+                last_error,
+            } => write!(
+                f,
+                "no valid answer after {} attempts: {}",
+                attempts, last_error
+            ),
+            PromptError::NoChoices => f.write_str("there is nothing to choose from"),
+        }
+    }
+}
+
+impl std::error::Error for PromptError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            PromptError::Read(e) => Some(e),
+            PromptError::Write(e) => Some(e),
+            PromptError::TooManyAttempts { .. } | PromptError::NoChoices => None,
+        }
+    }
+}
+
+impl From<ReadError> for PromptError {
+    fn from(e: ReadError) -> Self {
+        PromptError::Read(e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn ask<T>(prompt: Prompt<'_, T>, input: &str) -> (Result<T, PromptError>, String)
+    where
+        T: FromStr,
+        T::Err: fmt::Display,
+    {
+        let mut output = Vec::new();
+        let result = prompt.read_from(&mut Cursor::new(input), &mut output);
+        (result, String::from_utf8(output).unwrap())
+    }
+
+    #[test]
+    fn test_reasks_until_the_answer_parses() {
+        let (result, output) = ask(Prompt::<u8>::new("Age"), "old\n\n 42 \n");
+        assert_eq!(result.unwrap(), 42);
+        assert_eq!(
+            output,
+            "Age: invalid value \"old\": invalid digit found in string\n\
+             Age: an answer is required\n\
+             Age: "
+        );
+    }
+
+    #[test]
+    fn test_default_on_empty_answer_and_eof() {
+        let (result, output) = ask(Prompt::new("Name").default("anon".to_string()), "\n");
+        assert_eq!(result.unwrap(), "anon");
+        assert_eq!(output, "Name [anon]: ");
+        let (result, _) = ask(Prompt::new("Port").default(80u16), "");
+        assert_eq!(result.unwrap(), 80);
+        let (result, _) = ask(Prompt::<u16>::new("Port"), "");
+        assert!(matches!(result, Err(PromptError::Read(ReadError::Eof))));
+    }
+
+    #[test]
+    fn test_validator_and_retries() {
+        let even = |n: &i32| {
+            if n % 2 == 0 {
+                Ok(())
+            } else {
+                Err(format!("{} is odd", n))
+            }
+        };
+        let (result, output) = ask(Prompt::new("N").validate(even).retries(1), "3\n5\n4\n");
+        match result {
+            Err(PromptError::TooManyAttempts {
+                attempts,
+                last_error,
+            }) => {
+                assert_eq!(attempts, 2);
+                assert_eq!(last_error, "5 is odd");
+            }
+            other => panic!("expected too many attempts, got {:?}", other),
+        }
+        assert_eq!(output, "N: 3 is odd\nN: 5 is odd\n");
+    }
+
+    #[test]
+    fn test_confirm_reasks_until_yes_or_no() {
+        let mut output = Vec::new();
+        let answer = Confirm::new("Delete?", true)
+            .color(ColorChoice::Never)
+            .read_from(&mut Cursor::new("sure\nNo\n"), &mut output)
+            .unwrap();
+        assert!(!answer);
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "Delete? [Y/n] please answer y or n\nDelete? [Y/n] "
+        );
+    }
+
+    #[test]
+    fn test_confirm_defaults_and_assumed_answers() {
+        let mut output = Vec::new();
+        let confirm = Confirm::new("Delete?", false).color(ColorChoice::Never);
+        assert!(!confirm
+            .clone()
+            .read_from(&mut Cursor::new("\n"), &mut output)
+            .unwrap());
+        assert!(!confirm
+            .clone()
+            .read_from(&mut Cursor::new(""), &mut output)
+            .unwrap());
+        assert!(confirm
+            .clone()
+            .read_from(&mut Cursor::new(" Y \n"), &mut output)
+            .unwrap());
+
+        let mut output = Vec::new();
+        let answer = confirm
+            .assume(Some(true))
+            .read_from(&mut Cursor::new("n\n"), &mut output)
+            .unwrap();
+        assert!(answer);
+        assert!(output.is_empty());
+    }
+
+    #[test]
+    fn test_confirm_highlights_the_default() {
+        let mut output = Vec::new();
+        Confirm::new("Delete?", false)
+            .color(ColorChoice::Always)
+            .read_from(&mut Cursor::new("y\n"), &mut output)
+            .unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.starts_with("Delete? [y/\x1b["), "{:?}", output);
+        assert_eq!(crate::colors::strip_ansi(&output), "Delete? [y/N] ");
+    }
+}
@@ -0,0 +1,364 @@
+//! Changing terminal modes with termios, and putting them back.
+//!
+//! A mode change is undone when its guard is dropped, including while a panic unwinds.
+//! While a guard is alive, SIGINT, SIGTERM and SIGQUIT also restore the terminal when they
+//! are about to end the process, so Ctrl-C never leaves the shell without echo. A signal
+//! the program ignores or handles itself is passed on and leaves the mode as it is, so a
+//! password being typed stays hidden.
+//!
+//! Guards nest on one thread: each one puts back the settings it found, and only the
+//! outermost one deals with the signal handlers.
+
+use std::cell::{Cell, UnsafeCell};
+use std::io;
+use std::io::Write;
+use std::mem::MaybeUninit;
+use std::os::unix::io::RawFd;
+use std::sync::atomic::{AtomicBool, AtomicI32, Ordering};
+use std::sync::{Mutex, MutexGuard, TryLockError};
+
+const SIGNALS: [libc::c_int; 3] = [libc::SIGINT, libc::SIGTERM, libc::SIGQUIT];
+
+/// The state a signal handler needs to restore the terminal. It is written only while
+/// `GUARD_LOCK` is held and no handler is installed, and read only by the handler.
+struct Saved {
+    termios: UnsafeCell<MaybeUninit<libc::termios>>,
+    actions: UnsafeCell<MaybeUninit<[libc::sigaction; SIGNALS.len()]>>,
+}
+
+unsafe impl Sync for Saved {}
+
+static SAVED: Saved = Saved {
+    termios: UnsafeCell::new(MaybeUninit::uninit()),
+    actions: UnsafeCell::new(MaybeUninit::uninit()),
+};
+
+/// The descriptor whose settings are in `SAVED`, or -1 when no guard is alive.
+static SAVED_FD: AtomicI32 = AtomicI32::new(-1);
+
+/// Held by the outermost guard. The signal handlers restore one terminal, so guards can
+/// only be alive on one thread at a time.
+static GUARD_LOCK: Mutex<()> = Mutex::new(());
+
+/// Set while a `HiddenCursor` is alive, so a signal that ends the process shows it again.
+static CURSOR_HIDDEN: AtomicBool = AtomicBool::new(false);
+
+const SHOW_CURSOR: &[u8] = b"\x1b[?25h";
+
+thread_local! {
+    /// How many guards are alive on this thread.
+    static DEPTH: Cell<usize> = const { Cell::new(0) };
+}
+
+extern "C" fn on_signal(
+    signal: libc::c_int,
+    info: *mut libc::siginfo_t,
+    context: *mut libc::c_void,
+) {
+    // Only async-signal-safe calls are allowed here.
+    let Some(i) = SIGNALS.iter().position(|&s| s == signal) else {
+        return;
+    };
+    unsafe {
+        let old = &*((*SAVED.actions.get()).as_ptr() as *const libc::sigaction).add(i);
+        match old.sa_sigaction {
+            libc::SIG_DFL => {
+                let fd = SAVED_FD.swap(-1, Ordering::SeqCst);
+                if fd >= 0 {
+                    libc::tcsetattr(fd, libc::TCSANOW, (*SAVED.termios.get()).as_ptr());
+                }
+                if CURSOR_HIDDEN.swap(false, Ordering::SeqCst) {
+                    let show = SHOW_CURSOR.as_ptr() as *const libc::c_void;
+                    libc::write(libc::STDOUT_FILENO, show, SHOW_CURSOR.len());
+                }
+                libc::sigaction(signal, old, std::ptr::null_mut());
+                libc::raise(signal);
+            }
+            libc::SIG_IGN => {}
+            handler if old.sa_flags & libc::SA_SIGINFO != 0 => {
+                let handler: extern "C" fn(libc::c_int, *mut libc::siginfo_t, *mut libc::c_void) =
+                    std::mem::transmute(handler);
+                handler(signal, info, context);
+            }
+            handler => {
+                let handler: extern "C" fn(libc::c_int) = std::mem::transmute(handler);
+                handler(signal);
+            }
+        }
+    }
+}
+
+/// Returns the current settings of the terminal `fd`.
+pub(crate) fn get_attrs(fd: RawFd) -> io::Result<libc::termios> {
+    let mut termios = MaybeUninit::uninit();
+    if unsafe { libc::tcgetattr(fd, termios.as_mut_ptr()) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(unsafe { termios.assume_init() })
+}
+
+fn set_attrs(fd: RawFd, termios: &libc::termios) -> io::Result<()> {
+    if unsafe { libc::tcsetattr(fd, libc::TCSAFLUSH, termios) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+/// Restores the settings a terminal had when the guard was created.
+pub(crate) struct ModeGuard {
+    fd: RawFd,
+    previous: libc::termios,
+    /// Only the outermost guard holds the lock, and its drop uninstalls the handlers.
+    lock: Option<MutexGuard<'static, ()>>,
+}
+
+impl ModeGuard {
+    /// Applies `change` to the settings of the terminal `fd` until the guard is dropped.
+    ///
+    /// Fails if another thread has a guard alive, or if a guard for another terminal is
+    /// alive on this one.
+    pub(crate) fn new(fd: RawFd, change: impl FnOnce(&mut libc::termios)) -> io::Result<Self> {
+        let lock = if DEPTH.get() == 0 {
+            match GUARD_LOCK.try_lock() {
+                Ok(lock) => Some(lock),
+                Err(TryLockError::Poisoned(e)) => Some(e.into_inner()),
+                Err(TryLockError::WouldBlock) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::ResourceBusy,
+                        "another thread has changed the terminal mode",
+                    ))
+                }
+            }
+        } else if SAVED_FD.load(Ordering::SeqCst) != fd {
+            return Err(io::Error::new(
+                io::ErrorKind::ResourceBusy,
+                "another terminal's mode has been changed",
+            ));
+        } else {
+            None
+        };
+        let previous = get_attrs(fd)?;
+        if lock.is_some() {
+            unsafe {
+                (*SAVED.termios.get()).write(previous);
+                let mut handler: libc::sigaction = std::mem::zeroed();
+                handler.sa_sigaction = on_signal
+                    as extern "C" fn(libc::c_int, *mut libc::siginfo_t, *mut libc::c_void)
+                    as usize;
+                handler.sa_flags = libc::SA_SIGINFO;
+                libc::sigemptyset(&mut handler.sa_mask);
+                let actions = (*SAVED.actions.get()).as_mut_ptr() as *mut libc::sigaction;
+                SAVED_FD.store(fd, Ordering::SeqCst);
+                for (i, &signal) in SIGNALS.iter().enumerate() {
+                    libc::sigaction(signal, &handler, actions.add(i));
+                }
+            }
+        }
+        DEPTH.set(DEPTH.get() + 1);
+        let guard = ModeGuard { fd, previous, lock };
+        let mut changed = previous;
+        change(&mut changed);
+        set_attrs(fd, &changed)?;
+        Ok(guard)
+    }
+
+    /// Turns off echo, keeping the newline that ends the line visible.
+    pub(crate) fn no_echo(fd: RawFd) -> io::Result<Self> {
+        Self::new(fd, |termios| {
+            termios.c_lflag &= !libc::ECHO;
+            termios.c_lflag |= libc::ECHONL;
+        })
+    }
+
+    /// Turns off line buffering and echo, so every key press can be read as it happens.
+    /// Ctrl-C still interrupts the program.
+    pub(crate) fn raw(fd: RawFd) -> io::Result<Self> {
+        Self::new(fd, |termios| {
+            termios.c_lflag &= !(libc::ICANON | libc::ECHO | libc::IEXTEN);
+            termios.c_iflag &= !(libc::IXON | libc::ICRNL);
+            termios.c_cc[libc::VMIN] = 1;
+            termios.c_cc[libc::VTIME] = 0;
+        })
+    }
+}
+
+impl Drop for ModeGuard {
+    fn drop(&mut self) {
+        DEPTH.set(DEPTH.get() - 1);
+        if self.lock.is_some() {
+            unsafe {
+                let actions = (*SAVED.actions.get()).as_ptr() as *const libc::sigaction;
+                for (i, &signal) in SIGNALS.iter().enumerate() {
+                    libc::sigaction(signal, actions.add(i), std::ptr::null_mut());
+                }
+            }
+            SAVED_FD.store(-1, Ordering::SeqCst);
+        }
+        let _ = set_attrs(self.fd, &self.previous);
+    }
+}
+
+/// Hides the cursor of the terminal on stdout until it is dropped. A signal that ends the
+/// process while a `ModeGuard` is alive shows it again as well.
+pub(crate) struct HiddenCursor(());
+
+impl HiddenCursor {
+    pub(crate) fn new() -> io::Result<Self> {
+        let mut stdout = io::stdout();
+        stdout.write_all(b"\x1b[?25l")?;
+        stdout.flush()?;
+        CURSOR_HIDDEN.store(true, Ordering::SeqCst);
+        Ok(HiddenCursor(()))
+    }
+}
+
+impl Drop for HiddenCursor {
+    fn drop(&mut self) {
+        CURSOR_HIDDEN.store(false, Ordering::SeqCst);
+        let mut stdout = io::stdout();
+        let _ = stdout.write_all(SHOW_CURSOR);
+        let _ = stdout.flush();
+    }
+}
+
+/// Reads whatever bytes are available from `fd`, waiting for at least one.
+pub(crate) fn read_bytes(fd: RawFd, buf: &mut [u8]) -> io::Result<usize> {
+    loop {
+        let n = unsafe { libc::read(fd, buf.as_mut_ptr() as *mut libc::c_void, buf.len()) };
+        if n >= 0 {
+            return Ok(n as usize);
+        }
+        let error = io::Error::last_os_error();
+        if error.kind() != io::ErrorKind::Interrupted {
+            return Err(error);
+        }
+    }
+}
+
+/// Waits up to `timeout_ms` milliseconds for `fd` to have input, returning whether it has.
+pub(crate) fn wait_readable(fd: RawFd, timeout_ms: libc::c_int) -> io::Result<bool> {
+    let mut pollfd = libc::pollfd {
+        fd,
+        events: libc::POLLIN,
+        revents: 0,
+    };
+    loop {
+        let n = unsafe { libc::poll(&mut pollfd, 1, timeout_ms) };
+        if n >= 0 {
+            return Ok(n > 0);
+        }
+        let error = io::Error::last_os_error();
+        if error.kind() != io::ErrorKind::Interrupted {
+            return Err(error);
+        }
+    }
+}
+
+/// Returns the width of the terminal `fd` in columns, if it is a terminal that knows it.
+pub(crate) fn columns(fd: RawFd) -> Option<usize> {
+    let mut size: libc::winsize = unsafe { std::mem::zeroed() };
+    if unsafe { libc::ioctl(fd, libc::TIOCGWINSZ, &mut size) } != 0 || size.ws_col == 0 {
+        return None;
+    }
+    Some(size.ws_col as usize)
+}
+
+/// Serializes the tests that change terminal modes, which fail while another has a guard.
+#[cfg(all(test, target_os = "linux"))]
+static PTY_TESTS: Mutex<()> = Mutex::new(());
+
+/// Opens a pseudo-terminal pair for tests, returning the master and slave descriptors and
+/// a lock that keeps other such tests waiting until it is dropped.
+#[cfg(all(test, target_os = "linux"))]
+pub(crate) fn open_pty() -> (std::fs::File, std::fs::File, MutexGuard<'static, ()>) {
+    use std::os::unix::io::FromRawFd;
+    let serial = PTY_TESTS.lock().unwrap_or_else(|e| e.into_inner());
+    let (mut master, mut slave) = (-1, -1);
+    let result = unsafe {
+        libc::openpty(
+            &mut master,
+            &mut slave,
+            std::ptr::null_mut(),
+            std::ptr::null(),
+            std::ptr::null(),
+        )
+    };
+    assert_eq!(result, 0, "{}", io::Error::last_os_error());
+    unsafe {
+        (
+            std::fs::File::from_raw_fd(master),
+            std::fs::File::from_raw_fd(slave),
+            serial,
+        )
+    }
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+    use std::os::unix::io::AsRawFd;
+
+    #[test]
+    fn test_guard_restores_the_terminal_on_panic() {
+        let (_master, slave, _serial) = open_pty();
+        let fd = slave.as_raw_fd();
+        assert_ne!(get_attrs(fd).unwrap().c_lflag & libc::ECHO, 0);
+        let result = std::panic::catch_unwind(|| {
+            let _guard = ModeGuard::no_echo(fd).unwrap();
+            assert_eq!(get_attrs(fd).unwrap().c_lflag & libc::ECHO, 0);
+            panic!("interrupted");
+        });
+        assert!(result.is_err());
+        assert_ne!(get_attrs(fd).unwrap().c_lflag & libc::ECHO, 0);
+    }
+
+    #[test]
+    fn test_guards_nest_on_one_thread_only() {
+        let (_master, slave, _serial) = open_pty();
+        let fd = slave.as_raw_fd();
+        let raw = ModeGuard::raw(fd).unwrap();
+        let no_echo = ModeGuard::no_echo(fd).unwrap();
+        assert_eq!(get_attrs(fd).unwrap().c_lflag & libc::ECHO, 0);
+        let other = std::thread::spawn(move || ModeGuard::no_echo(fd).map(drop).unwrap_err());
+        assert_eq!(other.join().unwrap().kind(), io::ErrorKind::ResourceBusy);
+        drop(no_echo);
+        assert_eq!(get_attrs(fd).unwrap().c_lflag & libc::ICANON, 0);
+        drop(raw);
+        let attrs = get_attrs(fd).unwrap();
+        assert_ne!(attrs.c_lflag & libc::ICANON, 0);
+        assert_ne!(attrs.c_lflag & libc::ECHO, 0);
+    }
+
+    static HANDLED: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+
+    extern "C" fn set_handled(_: libc::c_int) {
+        HANDLED.store(true, Ordering::SeqCst);
+    }
+
+    #[test]
+    fn test_signals_the_program_handles_keep_echo_off() {
+        let (_master, slave, _serial) = open_pty();
+        let fd = slave.as_raw_fd();
+        let echo_off = || get_attrs(fd).unwrap().c_lflag & libc::ECHO == 0;
+        unsafe {
+            let original = libc::signal(libc::SIGINT, libc::SIG_IGN);
+            {
+                let _guard = ModeGuard::no_echo(fd).unwrap();
+                libc::raise(libc::SIGINT);
+                assert!(echo_off());
+            }
+            libc::signal(
+                libc::SIGINT,
+                set_handled as extern "C" fn(libc::c_int) as libc::sighandler_t,
+            );
+            {
+                let _guard = ModeGuard::no_echo(fd).unwrap();
+                libc::raise(libc::SIGINT);
+                assert!(HANDLED.load(Ordering::SeqCst));
+                assert!(echo_off());
+            }
+            libc::signal(libc::SIGINT, original);
+        }
+        assert!(!echo_off());
+    }
+}
@@ -1,12 +1,140 @@
-use cli_utils::colors::{ColorString, Color};
+use cli_utils::colors::{set_color_choice, Color, ColorChoice, ColorString, Style};
 
 #[test]
 fn test_red_coloring() {
+    set_color_choice(ColorChoice::Always);
     let mut color_string = ColorString {
-        color: Color::Red,
+        style: Color::Red.into(),
         string: "Red".to_string(),
         colorized: "".to_string(),
     };
     color_string.paint();
     assert_eq!(color_string.colorized, "\x1b[31mRed\x1b[0m");
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_reset_restores_plain_string() {
+    set_color_choice(ColorChoice::Always);
+    let mut color_string = ColorString {
+        style: Style::new().bold(),
+        string: "Bold".to_string(),
+        colorized: "".to_string(),
+    };
+    color_string.paint();
+    assert_eq!(color_string.colorized, "\x1b[1mBold\x1b[0m");
+    color_string.reset();
+    assert_eq!(color_string.colorized, "Bold");
+}
+
+#[test]
+fn test_bold_and_red_combine_into_one_sequence() {
+    set_color_choice(ColorChoice::Always);
+    let mut color_string = ColorString {
+        style: Style::new().fg(Color::Red).bold(),
+        string: "Error".to_string(),
+        colorized: "".to_string(),
+    };
+    color_string.paint();
+    assert_eq!(color_string.colorized, "\x1b[1;31mError\x1b[0m");
+}
+
+#[test]
+fn test_style_with_every_attribute_and_bright_colors() {
+    let style = Style::new()
+        .fg(Color::BrightWhite)
+        .bg(Color::BrightBlack)
+        .strikethrough()
+        .bold()
+        .dim()
+        .italic()
+        .underline()
+        .blink()
+        .reverse();
+    assert_eq!(style.prefix(), "\x1b[1;2;3;4;5;7;9;97;100m");
+}
+
+#[test]
+fn test_extended_colors_render_foreground_and_background() {
+    use cli_utils::colors::ColorDepth;
+
+    let style = Style::new()
+        .fg("#ff8800".parse().unwrap())
+        .bg(Color::Ansi256(236));
+    assert_eq!(
+        style.paint_for("brand", ColorDepth::TrueColor),
+        "\x1b[38;2;255;136;0;48;5;236mbrand\x1b[0m"
+    );
+    assert_eq!(
+        style.paint_for("brand", ColorDepth::Ansi256),
+        "\x1b[38;5;208;48;5;236mbrand\x1b[0m"
+    );
+}
+
+#[test]
+fn test_downsampling_keeps_basic_colors() {
+    use cli_utils::colors::ColorDepth;
+
+    let style = Style::new().fg(Color::Red).bg(Color::BrightBlue);
+    assert_eq!(style.prefix_for(ColorDepth::Ansi16), "\x1b[31;104m");
+    assert_eq!(
+        Color::Ansi256(9).downsample(ColorDepth::Ansi16),
+        Color::BrightRed
+    );
+    assert_eq!(
+        Color::Rgb(238, 238, 238).downsample(ColorDepth::Ansi256),
+        Color::Ansi256(255)
+    );
+}
+
+#[test]
+fn test_color_parsing_errors() {
+    assert!("#ggg".parse::<Color>().is_err());
+    assert!("256".parse::<Color>().is_err());
+    assert!("purple".parse::<Color>().is_err());
+    assert_eq!("BrightCyan".parse::<Color>().unwrap(), Color::BrightCyan);
+    assert_eq!("bright_cyan".parse::<Color>().unwrap(), Color::BrightCyan);
+    assert_eq!("Grey".parse::<Color>().unwrap(), Color::BrightBlack);
+}
+
+#[test]
+fn test_width_of_painted_text_ignores_escapes() {
+    use cli_utils::colors::{display_width, pad_to_width, red, strip_ansi};
+
+    set_color_choice(ColorChoice::Always);
+    let painted = red("ok");
+    assert_eq!(painted.len(), 11);
+    assert_eq!(strip_ansi(&painted), "ok");
+    assert_eq!(display_width(&painted), 2);
+    assert_eq!(display_width(&pad_to_width(&painted, 6)), 6);
+}
+
+#[test]
+fn test_markup_renders_like_the_helpers() {
+    use cli_utils::colors::{bold, markup, red};
+
+    set_color_choice(ColorChoice::Always);
+    assert_eq!(markup("[red]x[/]").unwrap(), red("x"));
+    assert_eq!(
+        markup("[red]x[/] [bold]y[/]").unwrap(),
+        format!("{} {}", red("x"), bold("y"))
+    );
+}
+
+#[test]
+fn test_theme_loaded_from_file() {
+    use cli_utils::colors::{Role, Theme};
+
+    set_color_choice(ColorChoice::Always);
+    let path = std::env::temp_dir().join(format!("cli-utils-theme-{}.conf", std::process::id()));
+    std::fs::write(&path, "base = high-contrast\nwarning = \"italic yellow\"\n").unwrap();
+    let theme = Theme::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(theme.name, "high-contrast");
+    assert_eq!(theme.warning("careful"), "\x1b[3;33mcareful\x1b[0m");
+    assert_eq!(
+        theme.style(Role::Error),
+        Theme::high_contrast().style(Role::Error)
+    );
+    assert!(Theme::load(path).is_err());
+}
@@ -0,0 +1,20 @@
+use cli_utils::History;
+
+#[test]
+fn test_history_survives_a_save_and_load() {
+    let path = std::env::temp_dir().join(format!("cli-utils-history-{}", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+
+    let mut history = History::new(3);
+    history.load(&path).unwrap();
+    assert!(history.is_empty());
+    for line in ["make", "make test", "git push", "exit"] {
+        history.add(line);
+    }
+    history.save(&path).unwrap();
+
+    let mut loaded = History::new(2);
+    loaded.load(&path).unwrap();
+    assert_eq!(loaded.iter().collect::<Vec<_>>(), ["git push", "exit"]);
+    std::fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,73 @@
+#![cfg(feature = "log")]
+
+use cli_utils::config::{LogLevel, LogOutput, Logging};
+
+#[test]
+fn test_level_conversions() {
+    assert_eq!(log::Level::from(LogLevel::Warn), log::Level::Warn);
+    assert_eq!(LogLevel::from(log::Level::Trace), LogLevel::Debug);
+    assert_eq!(
+        log::LevelFilter::from(LogLevel::Info),
+        log::LevelFilter::Info
+    );
+    assert_eq!(
+        LogLevel::try_from(log::LevelFilter::Error),
+        Ok(LogLevel::Error)
+    );
+    assert!(LogLevel::try_from(log::LevelFilter::Off).is_err());
+}
+
+#[test]
+fn test_third_party_records_reach_the_configured_file() {
+    let path = std::env::temp_dir().join(format!("cli-utils-facade-{}.log", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let config = Logging {
+        enabled: true,
+        level: LogLevel::Info,
+        destination: LogOutput::File(path.display().to_string()),
+        ..Logging::new()
+    };
+    config.init_log_facade().unwrap();
+    assert_eq!(log::max_level(), log::LevelFilter::Info);
+
+    log::debug!(target: "hyper::client", "filtered out");
+    log::info!(target: "hyper::client", "connected to {}", "example.com");
+    log::logger().flush();
+
+    let contents = std::fs::read_to_string(&path).unwrap();
+    assert_eq!(contents.lines().count(), 1);
+    assert!(contents.ends_with(" INFO  [hyper::client] connected to example.com\n"));
+
+    // A second facade cannot be registered, and must not take over the destination.
+    let other = std::env::temp_dir().join(format!("cli-utils-facade-{}-2.log", std::process::id()));
+    let second = Logging {
+        level: LogLevel::Debug,
+        destination: LogOutput::File(other.display().to_string()),
+        ..config.clone()
+    };
+    assert!(second.init_log_facade().is_err());
+    assert_eq!(log::max_level(), log::LevelFilter::Info);
+    log::info!(target: "hyper::client", "still here");
+    log::logger().flush();
+    let contents = std::fs::read_to_string(&path).unwrap();
+    assert!(contents.ends_with(" INFO  [hyper::client] still here\n"));
+    let _ = std::fs::remove_file(&other);
+
+    // A configuration installed later decides the level and what is let through.
+    let stricter = Logging {
+        level: LogLevel::Warn,
+        ..config.clone()
+    };
+    stricter.init().unwrap();
+    assert_eq!(log::max_level(), log::LevelFilter::Warn);
+    let info = log::Metadata::builder()
+        .level(log::Level::Info)
+        .target("hyper::client")
+        .build();
+    assert!(!log::logger().enabled(&info));
+    log::warn!(target: "hyper::client", "reconnecting");
+    log::logger().flush();
+    let contents = std::fs::read_to_string(&path).unwrap();
+    assert!(contents.ends_with(" WARN  [hyper::client] reconnecting\n"));
+    std::fs::remove_file(&path).unwrap();
+}
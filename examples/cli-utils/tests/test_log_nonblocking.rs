@@ -0,0 +1,77 @@
+use cli_utils::config::{flush, LogLevel, LogOutput, Logging, NonBlocking, Overflow};
+use cli_utils::info;
+use std::fs;
+use std::thread;
+
+// The logger is process-wide, so the scenarios run in sequence inside a single test.
+#[test]
+fn test_guard_writes_every_queued_record() {
+    let path =
+        std::env::temp_dir().join(format!("cli-utils-nonblocking-{}.log", std::process::id()));
+    let _ = fs::remove_file(&path);
+    let config = Logging {
+        enabled: true,
+        level: LogLevel::Info,
+        destination: LogOutput::File(path.display().to_string()),
+        ..Logging::new()
+    };
+
+    // A tiny queue makes the callers wait on the writer thread.
+    let guard = config
+        .init_nonblocking(NonBlocking {
+            capacity: 4,
+            batch_size: 3,
+            overflow: Overflow::Block,
+        })
+        .unwrap();
+    let threads: Vec<_> = (0..4)
+        .map(|t| {
+            thread::spawn(move || {
+                for i in 0..250 {
+                    info!(thread = t, record = i; "working");
+                }
+            })
+        })
+        .collect();
+    for thread in threads {
+        thread.join().unwrap();
+    }
+    info!("last line");
+    drop(guard);
+
+    let contents = fs::read_to_string(&path).unwrap();
+    let lines: Vec<&str> = contents.lines().collect();
+    assert_eq!(lines.len(), 1_001);
+    assert!(lines[1_000].ends_with("last line"), "{}", lines[1_000]);
+    for t in 0..4 {
+        // Records from one thread stay in the order they were logged.
+        let records: Vec<&str> = lines
+            .iter()
+            .filter(|l| l.contains(&format!(" thread={} ", t)))
+            .copied()
+            .collect();
+        assert_eq!(records.len(), 250);
+        assert!(records[249].ends_with(" record=249"), "{}", records[249]);
+    }
+
+    // Without the guard, records are written on the calling thread.
+    info!("after the guard");
+    flush();
+    let contents = fs::read_to_string(&path).unwrap();
+    assert!(contents.ends_with("after the guard\n"));
+
+    // With a flush, records are on disk while the guard is still alive.
+    let guard = config
+        .init_nonblocking(NonBlocking {
+            overflow: Overflow::DropNewest,
+            ..NonBlocking::new()
+        })
+        .unwrap();
+    info!("flushed");
+    flush();
+    let contents = fs::read_to_string(&path).unwrap();
+    assert!(contents.ends_with("flushed\n"));
+    assert_eq!(guard.dropped(), 0);
+    drop(guard);
+    fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,73 @@
+use cli_utils::config::{flush, LogLevel, LogOutput, Logging, Rotation, RotationPolicy};
+use cli_utils::info;
+use std::fs;
+use std::thread;
+
+#[test]
+fn test_concurrent_writers_lose_no_records_across_rotations() {
+    let dir = std::env::temp_dir().join(format!("cli-utils-rotation-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("app.log");
+
+    let config = Logging {
+        enabled: true,
+        level: LogLevel::Info,
+        destination: LogOutput::File(path.display().to_string()),
+        rotation: Rotation {
+            policy: RotationPolicy::MaxBytes(2_000),
+            keep: 1_000,
+            ..Rotation::new()
+        },
+        ..Logging::new()
+    };
+    config.init().unwrap();
+
+    let threads: Vec<_> = (0..8)
+        .map(|t| {
+            thread::spawn(move || {
+                for i in 0..100 {
+                    info!(thread = t, record = i; "working");
+                }
+            })
+        })
+        .collect();
+    for thread in threads {
+        thread.join().unwrap();
+    }
+    flush();
+
+    let mut lines = 0;
+    let mut files = 0;
+    for entry in fs::read_dir(&dir).unwrap() {
+        let contents = fs::read_to_string(entry.unwrap().path()).unwrap();
+        assert!(contents.len() <= 2_000);
+        assert!(contents.lines().all(|l| l.contains(" working thread=")));
+        lines += contents.lines().count();
+        files += 1;
+    }
+    assert_eq!(lines, 800);
+    assert!(files > 1);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[cfg(not(feature = "gzip"))]
+#[test]
+fn test_compression_without_the_feature_is_rejected() {
+    let config = Logging {
+        enabled: true,
+        destination: LogOutput::File(
+            std::env::temp_dir()
+                .join("cli-utils-gz.log")
+                .display()
+                .to_string(),
+        ),
+        rotation: Rotation {
+            policy: RotationPolicy::Daily,
+            compress: true,
+            ..Rotation::new()
+        },
+        ..Logging::new()
+    };
+    assert!(config.init().is_err());
+}
@@ -0,0 +1,72 @@
+use cli_utils::colors::ColorChoice;
+use cli_utils::config::{flush, LogFormat, LogLevel, LogOutput, Logging, Sink};
+use cli_utils::{debug, warn};
+use std::fs;
+use std::path::PathBuf;
+
+fn temp_log(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!(
+        "cli-utils-sinks-{}-{}.log",
+        name,
+        std::process::id()
+    ));
+    let _ = fs::remove_file(&path);
+    path
+}
+
+#[test]
+fn test_records_fan_out_to_every_sink() {
+    let text = temp_log("text");
+    let json = temp_log("json");
+    let config = Logging {
+        enabled: true,
+        level: LogLevel::Debug,
+        ..Logging::new()
+    }
+    .with_sink(Sink {
+        level: LogLevel::Info,
+        color: ColorChoice::Always,
+        ..Sink::new(LogOutput::File(text.display().to_string()))
+    })
+    .with_sink(Sink {
+        format: LogFormat::Json,
+        ..Sink::new(LogOutput::File(json.display().to_string()))
+    });
+    config.init().unwrap();
+
+    debug!(peer = 3; "handshake");
+    warn!("retrying");
+    flush();
+
+    let text_lines = fs::read_to_string(&text).unwrap();
+    let text_lines: Vec<&str> = text_lines.lines().collect();
+    assert_eq!(text_lines.len(), 1);
+    assert!(text_lines[0].contains("\x1b["), "{:?}", text_lines[0]);
+    assert!(
+        text_lines[0].ends_with(" [test_log_sinks] retrying"),
+        "{:?}",
+        text_lines[0]
+    );
+
+    let json_lines = fs::read_to_string(&json).unwrap();
+    let json_lines: Vec<&str> = json_lines.lines().collect();
+    assert_eq!(json_lines.len(), 2);
+    assert!(
+        json_lines[0].contains("\"level\":\"debug\""),
+        "{}",
+        json_lines[0]
+    );
+    assert!(
+        json_lines[0].ends_with("\"fields\":{\"peer\":3}}"),
+        "{}",
+        json_lines[0]
+    );
+    assert!(
+        json_lines[1].contains("\"message\":\"retrying\""),
+        "{}",
+        json_lines[1]
+    );
+
+    fs::remove_file(&text).unwrap();
+    fs::remove_file(&json).unwrap();
+}
@@ -0,0 +1,85 @@
+#![cfg(unix)]
+
+use cli_utils::config::{LogLevel, LogOutput, Logging, Sink};
+use cli_utils::{debug, warn};
+use std::os::unix::net::UnixDatagram;
+use std::path::PathBuf;
+use std::time::Duration;
+
+fn bind(name: &str) -> (UnixDatagram, PathBuf) {
+    let path = std::env::temp_dir().join(format!("cli-utils-{}-{}.sock", name, std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let socket = UnixDatagram::bind(&path).unwrap();
+    socket
+        .set_read_timeout(Some(Duration::from_secs(5)))
+        .unwrap();
+    (socket, path)
+}
+
+fn recv(socket: &UnixDatagram) -> Vec<u8> {
+    let mut buf = vec![0; 4096];
+    let n = socket.recv(&mut buf).unwrap();
+    buf.truncate(n);
+    buf
+}
+
+#[test]
+fn test_records_reach_syslog_and_journald_sockets() {
+    let (syslog, syslog_path) = bind("syslog");
+    let (journal, journal_path) = bind("journal");
+    let config = Logging {
+        enabled: true,
+        level: LogLevel::Debug,
+        ..Logging::new()
+    }
+    .with_sink(Sink {
+        level: LogLevel::Info,
+        ..Sink::new(LogOutput::Syslog(syslog_path.display().to_string()))
+    })
+    .with_sink(Sink::new(LogOutput::Journald(
+        journal_path.display().to_string(),
+    )));
+    config.init().unwrap();
+
+    debug!("only in the journal");
+    warn!(mount = "/var"; "disk almost full");
+
+    let entry = String::from_utf8(recv(&journal)).unwrap();
+    assert!(
+        entry.starts_with("MESSAGE=only in the journal\nPRIORITY=7\n"),
+        "{:?}",
+        entry
+    );
+    assert!(entry.ends_with("TARGET=test_log_syslog\n"), "{:?}", entry);
+    let entry = String::from_utf8(recv(&journal)).unwrap();
+    assert!(entry.contains("PRIORITY=4\n"), "{:?}", entry);
+    assert!(entry.ends_with("MOUNT=/var\n"), "{:?}", entry);
+
+    // The debug record is below the syslog sink's level.
+    let message = String::from_utf8(recv(&syslog)).unwrap();
+    assert!(message.starts_with("<12>1 "), "{:?}", message);
+    assert!(
+        message.ends_with(&format!(
+            " {} - - [test_log_syslog] disk almost full mount=/var",
+            std::process::id()
+        )),
+        "{:?}",
+        message
+    );
+
+    std::fs::remove_file(&syslog_path).unwrap();
+    std::fs::remove_file(&journal_path).unwrap();
+}
+
+#[test]
+fn test_output_names_round_trip() {
+    assert_eq!(
+        "journald".parse::<LogOutput>().unwrap(),
+        LogOutput::journald()
+    );
+    let output: LogOutput = "Syslog:/tmp/log.sock".parse().unwrap();
+    assert_eq!(output, LogOutput::Syslog("/tmp/log.sock".to_string()));
+    assert_eq!(output.to_string(), "syslog:/tmp/log.sock");
+    assert_eq!(LogOutput::syslog().to_string(), "syslog");
+    assert!("journald:".parse::<LogOutput>().is_err());
+}
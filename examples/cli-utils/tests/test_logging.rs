@@ -0,0 +1,147 @@
+use cli_utils::config::{flush, log_enabled, LogFormat, LogLevel, LogOutput, Logging};
+use cli_utils::{debug, error, info, warn};
+use std::path::PathBuf;
+
+fn temp_log(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("cli-utils-{}-{}.log", name, std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+// The logger is process-wide, so the scenarios run in sequence inside a single test.
+#[test]
+fn test_logger_filters_and_writes_to_file() {
+    let path = temp_log("file");
+    let config = Logging {
+        enabled: true,
+        level: LogLevel::Warn,
+        destination: LogOutput::File(path.display().to_string()),
+        ..Logging::new()
+    };
+    config.init().unwrap();
+    assert!(log_enabled(LogLevel::Error, "test_logging"));
+    assert!(!log_enabled(LogLevel::Info, "test_logging"));
+
+    debug!("not written");
+    info!("not written either");
+    warn!("low disk: {}%", 91);
+    error!("disk full");
+    flush();
+
+    let contents = std::fs::read_to_string(&path).unwrap();
+    let lines: Vec<&str> = contents.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert!(
+        lines[0].ends_with(" WARN  [test_logging] low disk: 91%"),
+        "{}",
+        lines[0]
+    );
+    assert!(
+        lines[1].ends_with(" ERROR [test_logging] disk full"),
+        "{}",
+        lines[1]
+    );
+    // 2024-03-01T09:05:00.042Z
+    assert_eq!(lines[0].find(' '), Some(24));
+    assert!(lines[0][..24].ends_with('Z'));
+
+    let disabled = Logging {
+        enabled: false,
+        ..config.clone()
+    };
+    disabled.init().unwrap();
+    assert!(!log_enabled(LogLevel::Error, "test_logging"));
+    error!("dropped");
+    flush();
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), contents);
+
+    let per_target = Logging {
+        enabled: true,
+        ..config.clone()
+    }
+    .with_directives("error,test_logging::sync=debug")
+    .unwrap();
+    per_target.init().unwrap();
+    cli_utils::config::log(
+        LogLevel::Debug,
+        "test_logging::sync",
+        format_args!("syncing"),
+    );
+    cli_utils::config::log(
+        LogLevel::Warn,
+        "test_logging::db",
+        format_args!("slow query"),
+    );
+    flush();
+    let contents = std::fs::read_to_string(&path).unwrap();
+    assert_eq!(contents.lines().count(), 3);
+    assert!(contents.ends_with(" DEBUG [test_logging::sync] syncing\n"));
+    std::fs::remove_file(&path).unwrap();
+
+    let json = Logging {
+        enabled: true,
+        format: LogFormat::Json,
+        ..config.clone()
+    };
+    json.init().unwrap();
+    warn!(user = "bob", attempts = 3; "login \"failed\"\nfor {}", "bob");
+    flush();
+    let contents = std::fs::read_to_string(&path).unwrap();
+    let json_tail = "\"level\":\"warn\",\"target\":\"test_logging\",\
+                     \"message\":\"login \\\"failed\\\"\\nfor bob\",\
+                     \"fields\":{\"user\":\"bob\",\"attempts\":3}}\n";
+    assert!(contents.starts_with("{\"timestamp\":\""), "{}", contents);
+    assert!(contents.ends_with(json_tail), "{}", contents);
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_init_reports_unopenable_file() {
+    let config = Logging {
+        enabled: true,
+        level: LogLevel::Info,
+        destination: LogOutput::File("/nonexistent-dir/app.log".to_string()),
+        ..Logging::new()
+    };
+    assert!(config.init().is_err());
+}
+
+#[test]
+fn test_levels_and_outputs_round_trip_through_strings() {
+    for level in [
+        LogLevel::Debug,
+        LogLevel::Info,
+        LogLevel::Warn,
+        LogLevel::Error,
+    ] {
+        assert_eq!(level.to_string().parse::<LogLevel>().unwrap(), level);
+        assert_eq!(
+            level
+                .to_string()
+                .to_uppercase()
+                .parse::<LogLevel>()
+                .unwrap(),
+            level
+        );
+    }
+    assert!(LogLevel::Debug < LogLevel::Info && LogLevel::Warn < LogLevel::Error);
+    assert_eq!(LogLevel::default(), LogLevel::Info);
+    assert_eq!(format!("[{:>5}]", LogLevel::Warn), "[ warn]");
+
+    let error = "verbose".parse::<LogLevel>().unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "invalid log level \"verbose\": expected one of debug, info, warn, error"
+    );
+
+    for output in [
+        LogOutput::Stdout,
+        LogOutput::Stderr,
+        LogOutput::File("logs/app.log".to_string()),
+    ] {
+        assert_eq!(output.to_string().parse::<LogOutput>().unwrap(), output);
+    }
+    assert_eq!("-".parse::<LogOutput>().unwrap(), LogOutput::Stdout);
+    assert!("".parse::<LogOutput>().is_err());
+}
@@ -0,0 +1,111 @@
+//! The functions under test read the real stdin, so each test runs itself again as a
+//! child process with its input piped in, and checks the lines the child printed.
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+const CHILD: &str = "CLI_UTILS_PIPED";
+
+/// Runs the test `name` in a child process with `input` on stdin, returning the lines it
+/// printed with `report`. The first one shares its line with the harness's `test ... `.
+fn run_piped(name: &str, input: &str) -> Vec<String> {
+    let mut child = Command::new(std::env::current_exe().unwrap())
+        .args([name, "--exact", "--nocapture", "--test-threads=1"])
+        .env(CHILD, "1")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::inherit())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(input.as_bytes())
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success(), "{} failed in the child", name);
+    String::from_utf8(output.stdout)
+        .unwrap()
+        .lines()
+        .filter_map(|line| line.split_once("> "))
+        .map(|(_, reported)| reported.to_string())
+        .collect()
+}
+
+fn in_child() -> bool {
+    std::env::var_os(CHILD).is_some()
+}
+
+fn report(line: &str) {
+    println!("> {}", line);
+}
+
+#[test]
+fn test_read_line_loop() {
+    if in_child() {
+        while let Some(line) = cli_utils::read_line() {
+            report(&line);
+        }
+        return;
+    }
+    assert_eq!(
+        run_piped("test_read_line_loop", "a\n  b \nc\n"),
+        ["a", "b", "c"]
+    );
+}
+
+#[test]
+fn test_prompts_in_a_row() {
+    if in_child() {
+        let name: String = cli_utils::prompt("Name").unwrap();
+        let age: u8 = cli_utils::prompt("Age").unwrap();
+        report(&format!("{} {}", name, age));
+        return;
+    }
+    assert_eq!(run_piped("test_prompts_in_a_row", "Ada\n36\n"), ["Ada 36"]);
+}
+
+#[test]
+fn test_line_after_a_password() {
+    if in_child() {
+        let password = cli_utils::read_password("Password: ").unwrap();
+        report(password.expose());
+        report(&cli_utils::read_line().unwrap());
+        return;
+    }
+    assert_eq!(
+        run_piped("test_line_after_a_password", " hunter2 \nnext\n"),
+        [" hunter2 ", "next"]
+    );
+}
+
+#[test]
+fn test_line_after_a_menu() {
+    if in_child() {
+        let choice = cli_utils::select("Deploy to", &["staging", "production"]).unwrap();
+        report(&choice.to_string());
+        report(&cli_utils::read_line().unwrap());
+        return;
+    }
+    assert_eq!(
+        run_piped("test_line_after_a_menu", "2\nnext\n"),
+        ["1", "next"]
+    );
+}
+
+#[test]
+fn test_line_editor_loop() {
+    if in_child() {
+        let mut editor = cli_utils::LineEditor::new();
+        while let Ok(line) = editor.read_line("$ ") {
+            report(&line);
+        }
+        report(&editor.history().len().to_string());
+        return;
+    }
+    assert_eq!(
+        run_piped("test_line_editor_loop", "ls\ncd src\n"),
+        ["ls", "cd src", "2"]
+    );
+}